@@ -20,6 +20,27 @@ pub struct Args {
     )]
     pub blocks: Vec<String>,
 
+    /// Select by time instead of by block number, can use dates,
+    /// datetimes, or unix timestamps, see syntax below
+    #[arg(
+        long,
+        value_name = "TIMESTAMPS",
+        allow_hyphen_values(true),
+        help_heading = "Content Options",
+        num_args(1..),
+        conflicts_with = "blocks",
+        verbatim_doc_comment
+    )]
+    pub timestamps: Option<Vec<String>>,
+
+    /// Sample a random fraction of blocks, e.g. 0.01, requires --seed
+    #[arg(long, value_name = "FRACTION", help_heading = "Content Options")]
+    pub sample: Option<f64>,
+
+    /// Seed of --sample, samples with the same seed are the same
+    #[arg(long, help_heading = "Content Options")]
+    pub seed: Option<u64>,
+
     /// Align block chunk boundaries to regular intervals,
     /// e.g. (1000, 2000, 3000) instead of (1106, 2106, 3106)
     #[arg(short, long, help_heading = "Content Options", verbatim_doc_comment)]
@@ -36,14 +57,28 @@ pub struct Args {
     )]
     pub reorg_buffer: u64,
 
-    // #[arg(
-    //     short,
-    //     long,
-    //     allow_hyphen_values(true),
-    //     help_heading = "Content Options",
-    //     help = "Select by data transaction instead of by block,\ncan be a list or a file, see
-    // syntax below", )]
-    // pub txs: Vec<String>,
+    /// Select by address instead of by block, or filter *_diffs,
+    /// can be a list or a file, see syntax below
+    #[arg(
+        long,
+        value_name = "ADDRESSES",
+        num_args(1..),
+        help_heading = "Content Options",
+        verbatim_doc_comment
+    )]
+    pub address: Option<Vec<String>>,
+
+    /// Select by transaction hash instead of by block,
+    /// can be a list or a file, see syntax below
+    #[arg(
+        long,
+        value_name = "TXS",
+        num_args(1..),
+        help_heading = "Content Options",
+        verbatim_doc_comment
+    )]
+    pub txs: Option<Vec<String>>,
+
     /// Columns to include alongside the default output,
     /// use `all` to include all available columns
     #[arg(short, long, value_name="COLS", num_args(0..), verbatim_doc_comment, help_heading="Content Options")]
@@ -74,6 +109,10 @@ pub struct Args {
     #[arg(long, help_heading = "Source Options")]
     pub network_name: Option<String>,
 
+    /// Trace namespace, `parity` or `geth` [default: detect from node]
+    #[arg(long, value_name = "BACKEND", help_heading = "Source Options")]
+    pub trace_backend: Option<String>,
+
     /// Ratelimit on requests per second
     #[arg(short('l'), long, value_name = "limit", help_heading = "Acquisition Options")]
     pub requests_per_second: Option<u32>,
@@ -141,27 +180,128 @@ pub struct Args {
     // /// [transactions] track gas used by each transaction
     // #[arg(long, help_heading = "Dataset-specific Options")]
     // pub gas_used: bool,
-    /// [logs] filter logs by contract address
-    #[arg(long, help_heading = "Dataset-specific Options")]
-    pub contract: Option<String>,
+    /// [logs, *_transfers, slots, eth_calls] filter by contract address,
+    /// can be a list or a file, see syntax below
+    #[arg(
+        long,
+        value_name = "ADDRESSES",
+        num_args(1..),
+        help_heading = "Dataset-specific Options",
+        verbatim_doc_comment
+    )]
+    pub contract: Option<Vec<String>>,
 
-    /// [logs] filter logs by topic0
-    #[arg(long, visible_alias = "event", help_heading = "Dataset-specific Options")]
-    pub topic0: Option<String>,
+    /// [logs] filter logs by topic0, can be a list or a file
+    #[arg(
+        long,
+        visible_alias = "event",
+        value_name = "TOPICS",
+        num_args(1..),
+        help_heading = "Dataset-specific Options"
+    )]
+    pub topic0: Option<Vec<String>>,
 
-    /// [logs] filter logs by topic1
-    #[arg(long, help_heading = "Dataset-specific Options")]
-    pub topic1: Option<String>,
+    /// [logs] filter logs by topic1, can be a list or a file
+    #[arg(long, value_name = "TOPICS", num_args(1..), help_heading = "Dataset-specific Options")]
+    pub topic1: Option<Vec<String>>,
 
-    /// [logs] filter logs by topic2
-    #[arg(long, help_heading = "Dataset-specific Options")]
-    pub topic2: Option<String>,
+    /// [logs] filter logs by topic2, can be a list or a file
+    #[arg(long, value_name = "TOPICS", num_args(1..), help_heading = "Dataset-specific Options")]
+    pub topic2: Option<Vec<String>>,
+
+    /// [logs] filter logs by topic3, can be a list or a file
+    #[arg(long, value_name = "TOPICS", num_args(1..), help_heading = "Dataset-specific Options")]
+    pub topic3: Option<Vec<String>>,
+
+    /// [logs] decode logs of an event,
+    /// e.g. "Transfer(address indexed, address indexed, uint256)"
+    #[arg(
+        long,
+        value_name = "SIGNATURE",
+        num_args(1..),
+        help_heading = "Dataset-specific Options",
+        verbatim_doc_comment
+    )]
+    pub event_signature: Option<Vec<String>>,
+
+    /// [txs, traces] decode call data of a function,
+    /// e.g. "transfer(address, uint256)(bool)"
+    #[arg(
+        long,
+        value_name = "SIGNATURE",
+        num_args(1..),
+        help_heading = "Dataset-specific Options",
+        verbatim_doc_comment
+    )]
+    pub function_signature: Option<Vec<String>>,
+
+    /// [logs, txs, traces] decode using abi json files or directories
+    #[arg(long, value_name = "PATHS", num_args(1..), help_heading = "Dataset-specific Options")]
+    pub abi: Option<Vec<String>>,
+
+    /// [txs, traces] filter by sender address,
+    /// can be a list or a file, see syntax below
+    #[arg(
+        long,
+        value_name = "ADDRESSES",
+        num_args(1..),
+        help_heading = "Dataset-specific Options",
+        verbatim_doc_comment
+    )]
+    pub from_address: Option<Vec<String>>,
+
+    /// [txs, traces] filter by receiver address,
+    /// can be a list or a file, see syntax below
+    #[arg(
+        long,
+        value_name = "ADDRESSES",
+        num_args(1..),
+        help_heading = "Dataset-specific Options",
+        verbatim_doc_comment
+    )]
+    pub to_address: Option<Vec<String>>,
+
+    /// [txs] filter by function selector of call data,
+    /// can be a list or a file, see syntax below
+    #[arg(
+        long,
+        value_name = "SELECTORS",
+        num_args(1..),
+        help_heading = "Dataset-specific Options",
+        verbatim_doc_comment
+    )]
+    pub function_selector: Option<Vec<String>>,
+
+    /// [vm_traces] filter by opcode, e.g. SSTORE
+    #[arg(long, value_name = "OPCODES", num_args(1..), help_heading = "Dataset-specific Options")]
+    pub opcode: Option<Vec<String>>,
+
+    /// [slots, storage_diffs] storage slots to read from --contract
+    /// or to filter by, can be a list or a file, see syntax below
+    #[arg(
+        long,
+        value_name = "SLOTS",
+        num_args(1..),
+        help_heading = "Dataset-specific Options",
+        verbatim_doc_comment
+    )]
+    pub slot: Option<Vec<String>>,
+
+    /// [eth_calls] function to call on --contract,
+    /// e.g. "balanceOf(address)(uint256)"
+    #[arg(
+        long,
+        value_name = "SIGNATURE",
+        help_heading = "Dataset-specific Options",
+        verbatim_doc_comment
+    )]
+    pub function: Option<String>,
 
-    /// [logs] filter logs by topic3
-    #[arg(long, help_heading = "Dataset-specific Options")]
-    pub topic3: Option<String>,
+    /// [eth_calls] inputs of the function call
+    #[arg(long, value_name = "INPUTS", num_args(1..), help_heading = "Dataset-specific Options")]
+    pub inputs: Option<Vec<String>>,
 
-    /// [logs] Number of blocks per log request
+    /// [logs, traces] Number of blocks per log or trace_filter request
     #[arg(
         long,
         value_name = "BLOCKS",
@@ -203,6 +343,22 @@ fn get_after_str() -> &'static str {
 - omitting range start means 0       <white><bold>:700</bold></white> == <white><bold>0:700</bold></white>
 - minus on start means minus end     <white><bold>-1000:7000</bold></white> == <white><bold>6000:7000</bold></white>
 - plus sign on end means plus start  <white><bold>15M:+1000</bold></white> == <white><bold>15M:15.001K</bold></white>
+- ranges can have a step             <white><bold>0:17M:1000</bold></white> == <white><bold>0 1000 2000 ... 17M</bold></white>
+- steps and samples suffix filenames <white><bold>0:17M:1000</bold></white> -> <white><bold>*__step1000.parquet</bold></white>
+- samples also suffix their seed     <white><bold>--sample 0.01 --seed 7</bold></white> -> <white><bold>*__sample0p01_seed7.parquet</bold></white>
+- can use dates or datetimes         <white><bold>--blocks 2023-01-01:2023-02-01</bold></white>
+- time ranges exclude their end      <white><bold>2023-06-01T00:00Z:+1d</bold></white> == <white><bold>2023-06-01:2023-06-02</bold></white>
+- durations can use { s m h d w }    <white><bold>2023-06-01:+2w</bold></white>
+- --timestamps also takes unix time  <white><bold>--timestamps 1685577600:+1h -12h:</bold></white>
+
+<white><bold>Address specification syntax</bold></white>
+- can use hex addresses              <white><bold>--address 0xd8da6bf2... 0xab5801a7...</bold></white>
+- can use parquet, csv, or txt files <white><bold>--address addresses.parquet</bold></white>
+- can select a column of a file      <white><bold>--address contracts.parquet:contract_address</bold></white>
+- blocks default to latest block     <white><bold>--address 0xd8da6bf2... --blocks 15M:+1000</bold></white>
+- --slot uses the same syntax        <white><bold>--slot 0x0 0x1 slots.csv:slot</bold></white>
+- --txs uses the same syntax         <white><bold>--txs txs.parquet:transaction_hash</bold></white>
+- filters use the same syntax        <white><bold>--contract 0xa0b86991... tokens.csv --topic1 0x... 0x...</bold></white>
 "#
     )
 }
@@ -212,8 +368,23 @@ fn get_datatype_help() -> &'static str {
         r#"datatype(s) to collect, one or more of:
 - <white><bold>blocks</bold></white>
 - <white><bold>transactions</bold></white>  (alias = <white><bold>txs</bold></white>)
+- <white><bold>receipts</bold></white>
+- <white><bold>withdrawals</bold></white>
+- <white><bold>blobs</bold></white>
+- <white><bold>uncles</bold></white>        (alias = <white><bold>ommers</bold></white>)
 - <white><bold>logs</bold></white>          (alias = <white><bold>events</bold></white>)
+- <white><bold>erc20_transfers</bold></white>
+- <white><bold>erc721_transfers</bold></white>
+- <white><bold>erc1155_transfers</bold></white>
 - <white><bold>traces</bold></white>        (alias = <white><bold>call_traces</bold></white>)
+- <white><bold>native_transfers</bold></white>
+- <white><bold>contracts</bold></white>
+- <white><bold>address_appearances</bold></white>
+- <white><bold>balances</bold></white>      (requires <white><bold>--address</bold></white>)
+- <white><bold>code</bold></white>          (requires <white><bold>--address</bold></white>)
+- <white><bold>nonces</bold></white>        (requires <white><bold>--address</bold></white>)
+- <white><bold>slots</bold></white>         (requires <white><bold>--contract</bold></white> and <white><bold>--slot</bold></white>)
+- <white><bold>eth_calls</bold></white>     (requires <white><bold>--contract</bold></white> and <white><bold>--function</bold></white>)
 - <white><bold>state_diffs</bold></white>   (= balance + code + nonce + storage diffs)
 - <white><bold>balance_diffs</bold></white>
 - <white><bold>code_diffs</bold></white>
@@ -0,0 +1,154 @@
+use cryo_freeze::{AddressChunk, Chunk, ParseError, TransactionChunk};
+use ethers::prelude::*;
+use polars::prelude::*;
+
+use crate::args::Args;
+
+/// parse address chunks to freeze, returns None if no addresses are given
+pub(crate) fn parse_address_chunks(args: &Args) -> Result<Option<Vec<AddressChunk>>, ParseError> {
+    let inputs = match &args.address {
+        Some(inputs) => inputs,
+        None => return Ok(None),
+    };
+    let addresses = parse_binary_inputs(inputs, "address")?;
+    if let Some(address) = addresses.iter().find(|address| address.len() != 20) {
+        return Err(ParseError::ParseError(format!("invalid address: 0x{}", hex::encode(address))))
+    }
+
+    let chunks = split_values(addresses, args).into_iter().map(AddressChunk::Values).collect();
+    Ok(Some(chunks))
+}
+
+/// parse transaction chunks to freeze, returns None if no transactions are given
+pub(crate) fn parse_transaction_chunks(args: &Args) -> Result<Option<Vec<Chunk>>, ParseError> {
+    let inputs = match &args.txs {
+        Some(inputs) => inputs,
+        None => return Ok(None),
+    };
+    let hashes = parse_binary_inputs(inputs, "transaction_hash")?;
+    if let Some(hash) = hashes.iter().find(|hash| hash.len() != 32) {
+        return Err(ParseError::ParseError(format!(
+            "invalid transaction hash: 0x{}",
+            hex::encode(hash)
+        )))
+    }
+
+    let chunks = split_values(hashes, args)
+        .into_iter()
+        .map(|values| Chunk::Transaction(TransactionChunk::Values(values)))
+        .collect();
+    Ok(Some(chunks))
+}
+
+/// parse addresses of a row filter, returns None if no addresses are given
+pub(crate) fn parse_address_filter(
+    inputs: &Option<Vec<String>>,
+    default_column: &str,
+) -> Result<Option<Vec<H160>>, ParseError> {
+    let inputs = match inputs {
+        Some(inputs) => inputs,
+        None => return Ok(None),
+    };
+    let addresses = parse_binary_inputs(inputs, default_column)?;
+    if addresses.is_empty() {
+        return Err(ParseError::ParseError(format!("no addresses in {}", inputs.join(" "))))
+    }
+    if let Some(address) = addresses.iter().find(|address| address.len() != 20) {
+        return Err(ParseError::ParseError(format!("invalid address: 0x{}", hex::encode(address))))
+    }
+    Ok(Some(addresses.iter().map(|address| H160::from_slice(address)).collect()))
+}
+
+/// split values into chunks according to --chunk-size or --n-chunks
+fn split_values(values: Vec<Vec<u8>>, args: &Args) -> Vec<Vec<Vec<u8>>> {
+    let chunk_size = match args.n_chunks {
+        Some(n_chunks) => {
+            let n_chunks = n_chunks.max(1);
+            ((values.len() as u64 + n_chunks - 1) / n_chunks).max(1)
+        }
+        None => args.chunk_size.max(1),
+    };
+    values.chunks(chunk_size as usize).map(|chunk| chunk.to_vec()).collect()
+}
+
+/// parse hex values given either directly or as files
+///
+/// files can be parquet, csv, or txt with one value per line, a column can be selected using
+/// `path:column` and otherwise defaults to `default_column`
+pub(crate) fn parse_binary_inputs(
+    inputs: &[String],
+    default_column: &str,
+) -> Result<Vec<Vec<u8>>, ParseError> {
+    let mut values = Vec::new();
+    for input in inputs.iter() {
+        if input.starts_with("0x") {
+            values.push(parse_hex(input)?);
+        } else {
+            values.extend(parse_binary_file(input, default_column)?);
+        }
+    }
+    Ok(values)
+}
+
+fn parse_binary_file(input: &str, default_column: &str) -> Result<Vec<Vec<u8>>, ParseError> {
+    let (path, column) = match input.rsplit_once(':') {
+        Some((path, column)) => (path, column),
+        None => (input, default_column),
+    };
+    let df = if path.ends_with(".parquet") {
+        let file = std::fs::File::open(path)
+            .map_err(|_e| ParseError::ParseError(format!("could not open file: {}", path)))?;
+        ParquetReader::new(file).finish()
+    } else if path.ends_with(".csv") {
+        CsvReader::from_path(path).and_then(|reader| reader.finish())
+    } else {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|_e| ParseError::ParseError(format!("could not open file: {}", path)))?;
+        return contents
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(parse_hex)
+            .collect()
+    };
+    let df = df.map_err(|_e| ParseError::ParseError(format!("could not read file: {}", path)))?;
+
+    let series = df
+        .column(column)
+        .map_err(|_e| ParseError::ParseError(format!("column {} not found in {}", column, path)))?;
+    match series.dtype() {
+        DataType::Binary => match series.binary() {
+            Ok(values) => Ok(values.into_iter().flatten().map(|value| value.to_vec()).collect()),
+            Err(_e) => Err(ParseError::ParseError(format!("could not read column {}", column))),
+        },
+        DataType::Utf8 => match series.utf8() {
+            Ok(values) => values.into_iter().flatten().map(parse_hex).collect(),
+            Err(_e) => Err(ParseError::ParseError(format!("could not read column {}", column))),
+        },
+        _ => Err(ParseError::ParseError(format!("column {} must be binary or hex", column))),
+    }
+}
+
+fn parse_hex(value: &str) -> Result<Vec<u8>, ParseError> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if digits.len() % 2 != 0 {
+        return Err(ParseError::ParseError(format!("odd length hex value: {}", value)))
+    }
+    hex::decode(digits)
+        .map_err(|_e| ParseError::ParseError(format!("invalid hex value: {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex_rejects_odd_length() {
+        assert_eq!(parse_hex("0x00").ok(), Some(vec![0]));
+        assert_eq!(parse_hex("0x0a0b").ok(), Some(vec![10, 11]));
+        assert!(parse_hex("0x0").is_err());
+        assert!(parse_hex("0x1").is_err());
+        assert!(parse_hex("0xabc").is_err());
+        assert!(parse_hex("0xzz").is_err());
+    }
+}
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 use ethers::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use cryo_freeze::{BlockChunk, Chunk, ChunkData, ParseError, Subchunk};
 
@@ -10,7 +12,15 @@ pub(crate) async fn parse_blocks(
     args: &Args,
     provider: Arc<Provider<Http>>,
 ) -> Result<Vec<Chunk>, ParseError> {
-    let block_chunks = parse_block_inputs(&args.blocks, &provider).await?;
+    check_timestamps(args)?;
+    let block_chunks = match &args.timestamps {
+        Some(timestamps) => parse_block_inputs(timestamps, true, &provider).await?,
+        None => parse_block_inputs(&args.blocks, false, &provider).await?,
+    };
+    let block_chunks = match parse_sample(args)? {
+        Some((fraction, seed)) => sample_blocks(block_chunks, fraction, seed),
+        None => block_chunks,
+    };
     let block_chunks = if args.align {
         block_chunks.into_iter().filter_map(|x| x.align(args.chunk_size)).collect()
     } else {
@@ -25,9 +35,39 @@ pub(crate) async fn parse_blocks(
     Ok(chunks)
 }
 
-/// parse block numbers to freeze
+/// parse block chunks at which address chunks are collected
+pub(crate) async fn parse_address_blocks(
+    args: &Args,
+    provider: Arc<Provider<Http>>,
+) -> Result<Vec<BlockChunk>, ParseError> {
+    check_timestamps(args)?;
+    // the default range would query every block for every address, use latest block instead
+    let (inputs, timestamps) = match &args.timestamps {
+        Some(timestamps) => (timestamps.clone(), true),
+        None if args.blocks == vec!["0:latest".to_string()] => (vec!["latest".to_string()], false),
+        None => (args.blocks.clone(), false),
+    };
+    let block_chunks = parse_block_inputs(&inputs, timestamps, &provider).await?;
+    match parse_sample(args)? {
+        Some((fraction, seed)) => Ok(sample_blocks(block_chunks, fraction, seed)),
+        None => Ok(block_chunks),
+    }
+}
+
+/// reject --timestamps alongside blocks other than the default --blocks
+fn check_timestamps(args: &Args) -> Result<(), ParseError> {
+    match (&args.timestamps, args.blocks == vec!["0:latest".to_string()]) {
+        (Some(_), false) => {
+            Err(ParseError::ParseError("cannot use both --blocks and --timestamps".to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// parse block numbers to freeze, inputs are times instead of block numbers if timestamps is set
 async fn parse_block_inputs(
     inputs: &Vec<String>,
+    timestamps: bool,
     provider: &Provider<Http>,
 ) -> Result<Vec<BlockChunk>, ParseError> {
     match inputs.len() {
@@ -35,12 +75,12 @@ async fn parse_block_inputs(
             let first_input = inputs.get(0).ok_or_else(|| {
                 ParseError::ParseError("Failed to get the first input".to_string())
             })?;
-            parse_block_token(first_input, true, provider).await.map(|x| vec![x])
+            parse_block_token(first_input, true, timestamps, provider).await.map(|x| vec![x])
         }
         _ => {
             let mut chunks = Vec::new();
             for input in inputs {
-                chunks.push(parse_block_token(input, false, provider).await?);
+                chunks.push(parse_block_token(input, false, timestamps, provider).await?);
             }
             Ok(chunks)
         }
@@ -56,17 +96,32 @@ enum RangePosition {
 async fn parse_block_token(
     s: &str,
     as_range: bool,
+    timestamps: bool,
     provider: &Provider<Http>,
 ) -> Result<BlockChunk, ParseError> {
     let s = s.replace('_', "");
-    let parts: Vec<&str> = s.split(':').collect();
+    let parts = split_block_token(&s);
+    let parts: Vec<&str> = parts.iter().map(|part| part.as_str()).collect();
     match parts.as_slice() {
+        [time_ref] if timestamps || is_date(time_ref) => {
+            let latest_block = get_latest_block_number(provider).await?;
+            let timestamp = parse_timestamp(time_ref, timestamps)?;
+            match count_blocks_before(timestamp, latest_block, provider).await? {
+                block if block > latest_block => {
+                    Err(ParseError::ParseError(format!("no blocks at or after {}", time_ref)))
+                }
+                block => Ok(BlockChunk::Numbers(vec![block])),
+            }
+        }
         [block_ref] => {
             let block = parse_block_number(block_ref, RangePosition::None, provider).await?;
             Ok(BlockChunk::Numbers(vec![block]))
         }
-        [first_ref, second_ref] => {
+        [first_ref, second_ref] | [first_ref, second_ref, _] => {
             let (start_block, end_block) = match (first_ref, second_ref) {
+                _ if timestamps || is_date(first_ref) || is_date(second_ref) => {
+                    parse_time_range(first_ref, second_ref, timestamps, provider).await?
+                }
                 _ if first_ref.starts_with('-') => {
                     let end_block =
                         parse_block_number(second_ref, RangePosition::Last, provider).await?;
@@ -98,10 +153,17 @@ async fn parse_block_token(
                 }
             };
 
+            let step = match parts.get(2) {
+                Some(step_ref) => Some(parse_step(step_ref)?),
+                None => None,
+            };
+
             if end_block <= start_block {
                 Err(ParseError::ParseError(
                     "end_block should not be less than start_block".to_string(),
                 ))
+            } else if let Some(step) = step {
+                Ok(BlockChunk::Numbers((start_block..=end_block).step_by(step as usize).collect()))
             } else if as_range {
                 Ok(BlockChunk::Range(start_block, end_block))
             } else {
@@ -109,11 +171,48 @@ async fn parse_block_token(
             }
         }
         _ => Err(ParseError::ParseError(
-            "blocks must be in format block_number or start_block:end_block".to_string(),
+            "blocks must be in format block_number or start_block:end_block[:step]".to_string(),
         )),
     }
 }
 
+/// parse the step of a block range, a positive integer that can use K and M suffixes
+fn parse_step(step_ref: &str) -> Result<u64, ParseError> {
+    let (number, scale) = match step_ref.char_indices().last() {
+        Some((index, 'K' | 'k')) => (&step_ref[..index], 1e3),
+        Some((index, 'M' | 'm')) => (&step_ref[..index], 1e6),
+        _ => (step_ref, 1.0),
+    };
+    match number.parse::<f64>().map(|n| n * scale) {
+        Ok(step) if step >= 1.0 && step.fract() == 0.0 => Ok(step as u64),
+        _ => Err(ParseError::ParseError(format!("step must be a positive integer: {}", step_ref))),
+    }
+}
+
+/// filename suffix of sparse block selections, so they do not collide with dense collections
+///
+/// encodes the steps of block ranges and the fraction and seed of --sample, e.g. sample0p01_seed7
+pub(crate) fn parse_sparse_suffix(args: &Args) -> Result<Option<String>, ParseError> {
+    let inputs = args.timestamps.as_ref().unwrap_or(&args.blocks);
+    let mut pieces: Vec<String> = Vec::new();
+    for input in inputs.iter() {
+        if let [_, _, step_ref] = split_block_token(&input.replace('_', "")).as_slice() {
+            let piece = format!("step{}", parse_step(step_ref)?);
+            if !pieces.contains(&piece) {
+                pieces.push(piece);
+            }
+        }
+    }
+    if let Some((fraction, seed)) = parse_sample(args)? {
+        pieces.push(format!("sample{}", fraction.to_string().replace('.', "p")));
+        pieces.push(format!("seed{}", seed));
+    }
+    match pieces.is_empty() {
+        true => Ok(None),
+        false => Ok(Some(pieces.join("_"))),
+    }
+}
+
 async fn parse_block_number(
     block_ref: &str,
     range_position: RangePosition,
@@ -155,6 +254,242 @@ async fn parse_block_number(
     }
 }
 
+/// split a block token on colons, keeping the colons within times such as 2023-06-01T00:00Z
+fn split_block_token(token: &str) -> Vec<String> {
+    let mut parts: Vec<String> = Vec::new();
+    for part in token.split(':') {
+        match parts.last_mut() {
+            Some(previous) if continues_time(previous, part) => {
+                previous.push(':');
+                previous.push_str(part);
+            }
+            _ => parts.push(part.to_string()),
+        }
+    }
+    parts
+}
+
+/// whether part holds the minutes, seconds, or offset minutes of the time in previous
+fn continues_time(previous: &str, part: &str) -> bool {
+    let time = match previous.split_once('T') {
+        Some((date, time)) if is_date(date) => time,
+        _ => return false,
+    };
+    let bytes = part.as_bytes();
+    let two_digits = bytes.len() >= 2 &&
+        bytes[..2].iter().all(|b| b.is_ascii_digit()) &&
+        !matches!(bytes.get(2), Some(b) if b.is_ascii_digit());
+    match time.find(|c: char| matches!(c, 'Z' | '+' | '-')) {
+        None => two_digits && time.split(':').count() < 3,
+        // minutes of offsets such as +01:00
+        Some(offset) => two_digits && part.len() == 2 && time[offset..].len() == 3,
+    }
+}
+
+/// whether a block reference is a date or datetime, e.g. 2023-06-01 or 2023-06-01T00:00Z
+fn is_date(block_ref: &str) -> bool {
+    let bytes = block_ref.as_bytes();
+    bytes.len() >= 10 &&
+        bytes[..4].iter().all(|b| b.is_ascii_digit()) &&
+        bytes[4] == b'-' &&
+        bytes[7] == b'-'
+}
+
+/// resolve a range of times to the blocks mined within it, the end of the range is exclusive
+async fn parse_time_range(
+    first_ref: &str,
+    second_ref: &str,
+    timestamps: bool,
+    provider: &Provider<Http>,
+) -> Result<(u64, u64), ParseError> {
+    let latest_block = get_latest_block_number(provider).await?;
+    let (start_time, end_time) = match (first_ref, second_ref) {
+        _ if first_ref.starts_with('-') => {
+            let end_time = parse_time_bound(
+                second_ref,
+                RangePosition::Last,
+                timestamps,
+                latest_block,
+                provider,
+            )
+            .await?;
+            let start_time = end_time
+                .checked_sub(parse_duration(&first_ref[1..])?)
+                .ok_or_else(|| ParseError::ParseError("start time underflow".to_string()))?;
+            (start_time, end_time)
+        }
+        _ if second_ref.starts_with('+') => {
+            let start_time = parse_time_bound(
+                first_ref,
+                RangePosition::First,
+                timestamps,
+                latest_block,
+                provider,
+            )
+            .await?;
+            let end_time = start_time
+                .checked_add(parse_duration(&second_ref[1..])?)
+                .ok_or_else(|| ParseError::ParseError("end time overflow".to_string()))?;
+            (start_time, end_time)
+        }
+        _ => {
+            let start_time = parse_time_bound(
+                first_ref,
+                RangePosition::First,
+                timestamps,
+                latest_block,
+                provider,
+            )
+            .await?;
+            let end_time = parse_time_bound(
+                second_ref,
+                RangePosition::Last,
+                timestamps,
+                latest_block,
+                provider,
+            )
+            .await?;
+            (start_time, end_time)
+        }
+    };
+
+    let start_block = count_blocks_before(start_time, latest_block, provider).await?;
+    if start_block > latest_block {
+        return Err(ParseError::ParseError(format!("no blocks at or after {}", first_ref)))
+    }
+    let end_block = count_blocks_before(end_time, latest_block, provider)
+        .await?
+        .checked_sub(1)
+        .ok_or_else(|| ParseError::ParseError(format!("no blocks before {}", second_ref)))?;
+    Ok((start_block, end_block))
+}
+
+async fn parse_time_bound(
+    time_ref: &str,
+    range_position: RangePosition,
+    timestamps: bool,
+    latest_block: u64,
+    provider: &Provider<Http>,
+) -> Result<u64, ParseError> {
+    match (time_ref, range_position) {
+        ("", RangePosition::First) => Ok(0),
+        // end of range is exclusive, so end just after the latest block
+        ("", _) | ("latest", _) => Ok(get_block_timestamp(latest_block, provider).await? + 1),
+        _ => parse_timestamp(time_ref, timestamps),
+    }
+}
+
+/// parse a date or datetime, or a unix timestamp if timestamps is set, times default to UTC
+fn parse_timestamp(time_ref: &str, timestamps: bool) -> Result<u64, ParseError> {
+    let naive = time_ref.strip_suffix('Z').unwrap_or(time_ref);
+    let timestamp = if let Ok(datetime) = DateTime::parse_from_rfc3339(time_ref) {
+        datetime.timestamp()
+    } else if let Ok(datetime) = DateTime::parse_from_str(time_ref, "%Y-%m-%dT%H:%M%:z") {
+        datetime.timestamp()
+    } else if let Ok(datetime) = NaiveDateTime::parse_from_str(naive, "%Y-%m-%dT%H:%M:%S") {
+        datetime.timestamp()
+    } else if let Ok(datetime) = NaiveDateTime::parse_from_str(naive, "%Y-%m-%dT%H:%M") {
+        datetime.timestamp()
+    } else if let Some(datetime) =
+        NaiveDate::parse_from_str(naive, "%Y-%m-%d").ok().and_then(|date| date.and_hms_opt(0, 0, 0))
+    {
+        datetime.timestamp()
+    } else if timestamps {
+        return time_ref
+            .parse::<u64>()
+            .map_err(|_e| ParseError::ParseError(format!("invalid timestamp: {}", time_ref)))
+    } else {
+        return Err(ParseError::ParseError(format!("invalid timestamp: {}", time_ref)))
+    };
+    u64::try_from(timestamp)
+        .map_err(|_e| ParseError::ParseError(format!("timestamp before 1970: {}", time_ref)))
+}
+
+/// parse a duration in seconds, e.g. 3600 30s 15m 12h 1d 2w
+fn parse_duration(duration: &str) -> Result<u64, ParseError> {
+    let (number, unit) = match duration.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        Some(index) => duration.split_at(index),
+        None => (duration, "s"),
+    };
+    let seconds = match unit {
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        "d" => 86400.0,
+        "w" => 604800.0,
+        _ => return Err(ParseError::ParseError(format!("invalid duration unit: {}", duration))),
+    };
+    number
+        .parse::<f64>()
+        .map(|n| (n * seconds) as u64)
+        .map_err(|_e| ParseError::ParseError(format!("invalid duration: {}", duration)))
+}
+
+/// number of blocks mined before a timestamp, found by binary search over block timestamps
+async fn count_blocks_before(
+    timestamp: u64,
+    latest_block: u64,
+    provider: &Provider<Http>,
+) -> Result<u64, ParseError> {
+    let (mut low, mut high) = (0, latest_block + 1);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        if get_block_timestamp(middle, provider).await? < timestamp {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    Ok(low)
+}
+
+async fn get_block_timestamp(number: u64, provider: &Provider<Http>) -> Result<u64, ParseError> {
+    match provider.get_block(number).await {
+        Ok(Some(block)) => Ok(block.timestamp.as_u64()),
+        _ => Err(ParseError::ParseError(format!("Error retrieving block {}", number))),
+    }
+}
+
+async fn get_latest_block_number(provider: &Provider<Http>) -> Result<u64, ParseError> {
+    provider
+        .get_block_number()
+        .await
+        .map(|n| n.as_u64())
+        .map_err(|_e| ParseError::ParseError("Error retrieving latest block number".to_string()))
+}
+
+/// fraction and seed of --sample, a seed is required so that the sample matches its filenames
+fn parse_sample(args: &Args) -> Result<Option<(f64, u64)>, ParseError> {
+    match (args.sample, args.seed) {
+        (Some(fraction), _) if !(0.0..=1.0).contains(&fraction) => {
+            Err(ParseError::ParseError("sample must be between 0 and 1".to_string()))
+        }
+        (Some(fraction), Some(seed)) => Ok(Some((fraction, seed))),
+        (Some(_), None) => Err(ParseError::ParseError("--sample requires --seed".to_string())),
+        (None, _) => Ok(None),
+    }
+}
+
+/// keep each block with probability fraction, the seed makes the sample reproducible
+fn sample_blocks(block_chunks: Vec<BlockChunk>, fraction: f64, seed: u64) -> Vec<BlockChunk> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut sampled = Vec::new();
+    for block_chunk in block_chunks.into_iter() {
+        let numbers: Vec<u64> = match block_chunk {
+            BlockChunk::Numbers(numbers) => {
+                numbers.into_iter().filter(|_| rng.gen_bool(fraction)).collect()
+            }
+            BlockChunk::Range(start, end) => {
+                (start..=end).filter(|_| rng.gen_bool(fraction)).collect()
+            }
+        };
+        if !numbers.is_empty() {
+            sampled.push(BlockChunk::Numbers(numbers));
+        }
+    }
+    sampled
+}
+
 async fn apply_reorg_buffer(
     block_chunks: Vec<BlockChunk>,
     reorg_filter: u64,
@@ -180,3 +515,71 @@ async fn apply_reorg_buffer(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn split_block_token_keeps_times_together() {
+        let cases = [
+            ("15M:+1000", vec!["15M", "+1000"]),
+            (":700", vec!["", "700"]),
+            ("0:17M:1000", vec!["0", "17M", "1000"]),
+            ("2023-01-01:2023-02-01", vec!["2023-01-01", "2023-02-01"]),
+            ("2023-06-01T00:00Z:+1d", vec!["2023-06-01T00:00Z", "+1d"]),
+            ("2023-06-01T00:00:30:+1d", vec!["2023-06-01T00:00:30", "+1d"]),
+            ("2023-06-01T00:00+01:00:15", vec!["2023-06-01T00:00+01:00", "15"]),
+            (
+                "2023-06-01T00:00:00Z:2023-06-02T00:00Z",
+                vec!["2023-06-01T00:00:00Z", "2023-06-02T00:00Z"],
+            ),
+            (
+                "2023-06-01T00:00:30:2023-06-02:3600",
+                vec!["2023-06-01T00:00:30", "2023-06-02", "3600"],
+            ),
+        ];
+        for (token, expected) in cases {
+            assert_eq!(split_block_token(token), expected, "token: {}", token);
+        }
+    }
+
+    #[test]
+    fn parse_step_accepts_integers_with_suffixes() {
+        assert_eq!(parse_step("1000").ok(), Some(1000));
+        assert_eq!(parse_step("1K").ok(), Some(1000));
+        assert_eq!(parse_step("1.5k").ok(), Some(1500));
+        assert_eq!(parse_step("2M").ok(), Some(2_000_000));
+        assert!(parse_step("0").is_err());
+        assert!(parse_step("1.5").is_err());
+        assert!(parse_step("latest").is_err());
+    }
+
+    #[test]
+    fn continues_time_stops_after_seconds_and_offsets() {
+        assert!(continues_time("2023-06-01T00", "00Z"));
+        assert!(continues_time("2023-06-01T00:00", "30"));
+        assert!(!continues_time("2023-06-01T00:00:30", "15"));
+        assert!(continues_time("2023-06-01T00:00+01", "00"));
+        assert!(!continues_time("2023-06-01T00:00+01:00", "15"));
+        assert!(!continues_time("2023-06-01T00:00Z", "+1d"));
+        assert!(!continues_time("2023-06-01", "30"));
+        assert!(!continues_time("15M", "00"));
+    }
+
+    #[test]
+    fn sparse_suffix_encodes_steps_and_samples_without_dots() {
+        let suffix = |inputs: &[&str]| {
+            let args = Args::parse_from([&["cryo", "blocks"][..], inputs].concat());
+            parse_sparse_suffix(&args).map_err(|e| format!("{:?}", e))
+        };
+        assert_eq!(suffix(&[]), Ok(None));
+        assert_eq!(suffix(&["-b", "0:1000:10", "5:100:10"]), Ok(Some("step10".to_string())));
+        let sampled = suffix(&["--sample", "0.01", "--seed", "7"]);
+        assert_eq!(sampled, Ok(Some("sample0p01_seed7".to_string())));
+        assert!(suffix(&["--sample", "0.01"]).is_err());
+        assert!(suffix(&["--sample", "2", "--seed", "7"]).is_err());
+    }
+}
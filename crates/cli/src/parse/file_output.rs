@@ -4,6 +4,7 @@ use polars::prelude::*;
 
 use cryo_freeze::{FileFormat, FileOutput, ParseError, Source};
 
+use super::blocks;
 use crate::args::Args;
 
 pub(crate) fn parse_file_output(args: &Args, source: &Source) -> Result<FileOutput, ParseError> {
@@ -19,7 +20,11 @@ pub(crate) fn parse_file_output(args: &Args, source: &Source) -> Result<FileOutp
         Err(e) => return Err(ParseError::ParseError(format!("Error creating directory: {}", e))),
     };
 
-    let file_suffix = &args.file_suffix;
+    let file_suffix = match (blocks::parse_sparse_suffix(args)?, &args.file_suffix) {
+        (Some(sparse), Some(suffix)) => Some(format!("{}__{}", sparse, suffix)),
+        (Some(sparse), None) => Some(sparse),
+        (None, suffix) => suffix.clone(),
+    };
 
     let parquet_compression = parse_compression(&args.compression)?;
 
@@ -38,7 +43,7 @@ pub(crate) fn parse_file_output(args: &Args, source: &Source) -> Result<FileOutp
         overwrite: args.overwrite,
         prefix: file_prefix,
         format,
-        suffix: file_suffix.clone(),
+        suffix: file_suffix,
         parquet_compression,
         row_group_size,
     };
@@ -1,7 +1,9 @@
+mod addresses;
 mod args;
 mod blocks;
 mod file_output;
 mod query;
+mod signatures;
 mod source;
 
 pub use args::*;
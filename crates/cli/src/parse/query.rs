@@ -1,33 +1,204 @@
 use std::{collections::HashMap, sync::Arc};
 
-use ethers::prelude::*;
-use hex::FromHex;
+use ethers::{
+    abi::{Event, Function},
+    prelude::*,
+    types::Opcode,
+};
 
-use cryo_freeze::{ColumnEncoding, Datatype, FileFormat, MultiQuery, ParseError, RowFilter, Table};
+use cryo_freeze::{
+    Chunk, ChunkData, ColumnEncoding, Datatype, FileFormat, MultiQuery, ParseError, RowFilter,
+    Subchunk, Table,
+};
 
-use super::{blocks, file_output};
+use super::{addresses, blocks, file_output, signatures};
 use crate::args::Args;
 
 pub(crate) async fn parse_query(
     args: &Args,
     provider: Arc<Provider<Http>>,
 ) -> Result<MultiQuery, ParseError> {
-    let chunks = blocks::parse_blocks(args, provider).await?;
-
     // process schemas
-    let schemas = parse_schemas(args)?;
+    let events = parse_events(args)?;
+    let functions = parse_functions(args)?;
+    let schemas = parse_schemas(args, &events, &functions)?;
+
+    // datasets that are collected by address instead of by block
+    let address_datatypes = [Datatype::Balances, Datatype::Code, Datatype::Nonces];
+    // datasets that can be collected by transaction instead of by block
+    let transaction_datatypes = [
+        Datatype::Transactions,
+        Datatype::Receipts,
+        Datatype::Traces,
+        Datatype::BalanceDiffs,
+        Datatype::CodeDiffs,
+        Datatype::NonceDiffs,
+        Datatype::StorageDiffs,
+        Datatype::VmTraces,
+    ];
+    // datasets whose rows are filtered by --address instead of collected by address
+    let diff_datatypes =
+        [Datatype::BalanceDiffs, Datatype::CodeDiffs, Datatype::NonceDiffs, Datatype::StorageDiffs];
+    let filter_by_address = schemas.keys().all(|d| diff_datatypes.contains(d));
+    if let Some(datatype) = schemas.keys().find(|d| diff_datatypes.contains(d)) {
+        if args.address.is_some() && !filter_by_address {
+            return Err(ParseError::ParseError(format!(
+                "--address filters {} but selects addresses of other datasets, collect them in separate queries",
+                datatype.dataset().name()
+            )))
+        }
+    }
+    let address_chunks = match filter_by_address {
+        true => None,
+        false => addresses::parse_address_chunks(args)?,
+    };
+    let transaction_chunks = addresses::parse_transaction_chunks(args)?;
+    let chunks = match (address_chunks, transaction_chunks) {
+        (Some(_), Some(_)) => {
+            return Err(ParseError::ParseError("cannot use both --address and --txs".to_string()))
+        }
+        (Some(address_chunks), None) => {
+            if let Some(datatype) = schemas.keys().find(|d| !address_datatypes.contains(d)) {
+                return Err(ParseError::ParseError(format!(
+                    "{} cannot be collected by --address",
+                    datatype.dataset().name()
+                )))
+            }
+            let block_chunks = blocks::parse_address_blocks(args, provider).await?;
+            let mut chunks = Vec::new();
+            for address_chunk in address_chunks.into_iter() {
+                // each chunk holds at most --chunk-size (address, block) pairs
+                let n_blocks = (args.chunk_size / address_chunk.size().max(1)).max(1);
+                for block_chunk in block_chunks.subchunk_by_size(&n_blocks).into_iter() {
+                    chunks.push(Chunk::Address(address_chunk.clone(), block_chunk));
+                }
+            }
+            chunks
+        }
+        (None, Some(transaction_chunks)) => {
+            if let Some(datatype) = schemas.keys().find(|d| !transaction_datatypes.contains(d)) {
+                return Err(ParseError::ParseError(format!(
+                    "{} cannot be collected by --txs",
+                    datatype.dataset().name()
+                )))
+            }
+            transaction_chunks
+        }
+        (None, None) => {
+            if let Some(datatype) = schemas.keys().find(|d| address_datatypes.contains(d)) {
+                return Err(ParseError::ParseError(format!(
+                    "{} requires --address",
+                    datatype.dataset().name()
+                )))
+            }
+            blocks::parse_blocks(args, provider).await?
+        }
+    };
 
     // build row filters
-    let contract = parse_address(&args.contract);
+    let contract = parse_address(&args.contract)?;
     let topics = [
-        parse_topic(&args.topic0),
-        parse_topic(&args.topic1),
-        parse_topic(&args.topic2),
-        parse_topic(&args.topic3),
+        parse_topic(&args.topic0, "topic0")?,
+        parse_topic(&args.topic1, "topic1")?,
+        parse_topic(&args.topic2, "topic2")?,
+        parse_topic(&args.topic3, "topic3")?,
     ];
-    let row_filter = RowFilter { address: contract, topics };
+    let row_filter = RowFilter { address: contract.clone(), topics, ..Default::default() };
     let mut row_filters: HashMap<Datatype, RowFilter> = HashMap::new();
-    row_filters.insert(Datatype::Logs, row_filter);
+    for datatype in [
+        Datatype::Logs,
+        Datatype::Erc20Transfers,
+        Datatype::Erc721Transfers,
+        Datatype::Erc1155Transfers,
+    ] {
+        row_filters.insert(datatype, row_filter.clone());
+    }
+    if let Some(events) = events {
+        // topic0 of logs defaults to the signatures of the decoded events
+        let mut log_filter = row_filter.clone();
+        if log_filter.topics[0].is_none() {
+            let mut topic0 = Vec::new();
+            for signature in events.iter().map(|event| Some(event.signature())) {
+                if !topic0.contains(&signature) {
+                    topic0.push(signature);
+                }
+            }
+            log_filter.topics[0] = Some(ValueOrArray::Array(topic0));
+        }
+        log_filter.events = Some(events);
+        row_filters.insert(Datatype::Logs, log_filter);
+    }
+    if let Some(functions) = functions {
+        let function_filter = RowFilter { functions: Some(functions), ..Default::default() };
+        for datatype in [Datatype::Transactions, Datatype::Traces] {
+            row_filters.insert(datatype, function_filter.clone());
+        }
+    }
+    let from_address = addresses::parse_address_filter(&args.from_address, "from_address")?;
+    let to_address = addresses::parse_address_filter(&args.to_address, "to_address")?;
+    for datatype in [Datatype::Transactions, Datatype::Traces] {
+        if from_address.is_some() || to_address.is_some() {
+            let filter = row_filters.entry(datatype).or_default();
+            filter.from_address = from_address.clone();
+            filter.to_address = to_address.clone();
+        }
+    }
+    if let Some(selectors) = &args.function_selector {
+        let filter = row_filters.entry(Datatype::Transactions).or_default();
+        filter.function_selectors = Some(parse_function_selectors(selectors)?);
+    }
+    if filter_by_address {
+        let address = addresses::parse_address_filter(&args.address, "address")?;
+        let slots = match &args.slot {
+            Some(slots) => Some(parse_slots(slots)?),
+            None => None,
+        };
+        for datatype in diff_datatypes {
+            let diff_filter = RowFilter {
+                address: address.clone().map(ValueOrArray::Array),
+                slots: if datatype == Datatype::StorageDiffs { slots.clone() } else { None },
+                ..Default::default()
+            };
+            row_filters.insert(datatype, diff_filter);
+        }
+    }
+    if let Some(opcodes) = &args.opcode {
+        let opcode_filter =
+            RowFilter { opcodes: Some(parse_opcodes(opcodes)?), ..Default::default() };
+        row_filters.insert(Datatype::VmTraces, opcode_filter);
+    }
+    if schemas.contains_key(&Datatype::Slots) {
+        let slots = match &args.slot {
+            Some(slots) if contract.is_some() => parse_slots(slots)?,
+            _ => {
+                return Err(ParseError::ParseError(
+                    "slots requires --contract and --slot".to_string(),
+                ))
+            }
+        };
+        let slot_filter =
+            RowFilter { address: contract.clone(), slots: Some(slots), ..Default::default() };
+        row_filters.insert(Datatype::Slots, slot_filter);
+    }
+    if schemas.contains_key(&Datatype::EthCalls) {
+        let function = match (&args.function, &contract) {
+            (Some(function), Some(_)) => signatures::parse_function_signature(function)?,
+            _ => {
+                return Err(ParseError::ParseError(
+                    "eth_calls requires --contract and --function".to_string(),
+                ))
+            }
+        };
+        let inputs = args.inputs.clone().unwrap_or_default();
+        let call_data = signatures::encode_function_call(&function, &inputs)?;
+        let call_filter = RowFilter {
+            address: contract,
+            function: Some(function),
+            call_data: Some(call_data),
+            ..Default::default()
+        };
+        row_filters.insert(Datatype::EthCalls, call_filter);
+    }
 
     let query = MultiQuery { schemas, chunks, row_filters };
     Ok(query)
@@ -46,17 +217,33 @@ fn parse_datatypes(raw_inputs: &Vec<String>) -> Result<Vec<Datatype>, ParseError
             }
             datatype => {
                 let datatype = match datatype {
+                    "address_appearances" => Datatype::AddressAppearances,
                     "balance_diffs" => Datatype::BalanceDiffs,
+                    "balances" => Datatype::Balances,
+                    "blobs" => Datatype::Blobs,
                     "blocks" => Datatype::Blocks,
+                    "code" => Datatype::Code,
                     "code_diffs" => Datatype::CodeDiffs,
+                    "contracts" => Datatype::Contracts,
+                    "erc20_transfers" => Datatype::Erc20Transfers,
+                    "erc721_transfers" => Datatype::Erc721Transfers,
+                    "erc1155_transfers" => Datatype::Erc1155Transfers,
+                    "eth_calls" => Datatype::EthCalls,
                     "logs" => Datatype::Logs,
                     "events" => Datatype::Logs,
+                    "native_transfers" => Datatype::NativeTransfers,
                     "nonce_diffs" => Datatype::NonceDiffs,
+                    "nonces" => Datatype::Nonces,
+                    "receipts" => Datatype::Receipts,
+                    "slots" => Datatype::Slots,
                     "storage_diffs" => Datatype::StorageDiffs,
                     "transactions" => Datatype::Transactions,
                     "txs" => Datatype::Transactions,
                     "traces" => Datatype::Traces,
+                    "uncles" => Datatype::Uncles,
+                    "ommers" => Datatype::Uncles,
                     "vm_traces" => Datatype::VmTraces,
+                    "withdrawals" => Datatype::Withdrawals,
                     "opcode_traces" => Datatype::VmTraces,
                     _ => {
                         return Err(ParseError::ParseError(format!("invalid datatype {}", datatype)))
@@ -69,7 +256,66 @@ fn parse_datatypes(raw_inputs: &Vec<String>) -> Result<Vec<Datatype>, ParseError
     Ok(datatypes)
 }
 
-fn parse_schemas(args: &Args) -> Result<HashMap<Datatype, Table>, ParseError> {
+/// events to decode logs with, given by --event-signature or --abi
+fn parse_events(args: &Args) -> Result<Option<Vec<Event>>, ParseError> {
+    let mut events = Vec::new();
+    for signature in args.event_signature.iter().flatten() {
+        events.push(signatures::parse_event_signature(signature)?);
+    }
+    for abi in signatures::load_abis(args.abi.as_deref().unwrap_or_default())?.iter() {
+        events.extend(abi.events().filter(|event| !event.anonymous).cloned());
+    }
+    match events.is_empty() {
+        true => Ok(None),
+        false => Ok(Some(events)),
+    }
+}
+
+/// functions to decode call data with, given by --function-signature or --abi
+fn parse_functions(args: &Args) -> Result<Option<Vec<Function>>, ParseError> {
+    let mut functions = Vec::new();
+    for signature in args.function_signature.iter().flatten() {
+        functions.push(signatures::parse_function_signature(signature)?);
+    }
+    for abi in signatures::load_abis(args.abi.as_deref().unwrap_or_default())?.iter() {
+        functions.extend(abi.functions().cloned());
+    }
+    check_selector_clashes(&functions)?;
+    match functions.is_empty() {
+        true => Ok(None),
+        false => Ok(Some(functions)),
+    }
+}
+
+/// distinct functions sharing a selector could not be told apart when decoding
+fn check_selector_clashes(functions: &[Function]) -> Result<(), ParseError> {
+    for (index, function) in functions.iter().enumerate() {
+        let clash = functions[..index].iter().find(|other| {
+            other.short_signature() == function.short_signature() &&
+                (other.name != function.name ||
+                    !other
+                        .inputs
+                        .iter()
+                        .map(|p| &p.kind)
+                        .eq(function.inputs.iter().map(|p| &p.kind)))
+        });
+        if let Some(other) = clash {
+            return Err(ParseError::ParseError(format!(
+                "functions {} and {} have the same selector 0x{}",
+                other.signature(),
+                function.signature(),
+                hex::encode(function.short_signature())
+            )))
+        }
+    }
+    Ok(())
+}
+
+fn parse_schemas(
+    args: &Args,
+    events: &Option<Vec<Event>>,
+    functions: &Option<Vec<Function>>,
+) -> Result<HashMap<Datatype, Table>, ParseError> {
     let datatypes = parse_datatypes(&args.datatype)?;
     let output_format = file_output::parse_output_format(args)?;
     let binary_column_format = match args.hex | (output_format != FileFormat::Parquet) {
@@ -81,26 +327,68 @@ fn parse_schemas(args: &Args) -> Result<HashMap<Datatype, Table>, ParseError> {
     let schemas: Result<HashMap<Datatype, Table>, ParseError> = datatypes
         .iter()
         .map(|datatype| {
-            datatype
+            let include_columns = match (datatype, functions) {
+                (Datatype::Transactions, Some(_)) => {
+                    with_columns(&args.include_columns, &FUNCTION_COLUMNS[..3])
+                }
+                (Datatype::Traces, Some(_)) => {
+                    with_columns(&args.include_columns, &FUNCTION_COLUMNS)
+                }
+                _ => args.include_columns.clone(),
+            };
+            // decoded event columns are selected separately from the columns of the dataset
+            let columns = match (datatype, events, &args.columns) {
+                (Datatype::Logs, Some(_), Some(columns)) => Some(
+                    columns
+                        .iter()
+                        .filter(|column| !column.starts_with("event__"))
+                        .cloned()
+                        .collect(),
+                ),
+                _ => args.columns.clone(),
+            };
+            let mut schema = datatype
                 .table_schema(
                     &binary_column_format,
-                    &args.include_columns,
+                    &include_columns,
                     &args.exclude_columns,
-                    &args.columns,
+                    &columns,
                     sort[datatype].clone(),
                 )
-                .map(|schema| (*datatype, schema))
                 .map_err(|_e| {
                     ParseError::ParseError(format!(
                         "Failed to get schema for datatype: {:?}",
                         datatype
                     ))
-                })
+                })?;
+            if let (Datatype::Logs, Some(events)) = (datatype, events) {
+                schema
+                    .add_event_columns(
+                        events,
+                        &binary_column_format,
+                        &args.columns,
+                        &args.exclude_columns,
+                    )
+                    .map_err(|e| ParseError::ParseError(e.to_string()))?;
+            }
+            Ok((*datatype, schema))
         })
         .collect();
     schemas
 }
 
+/// columns of decoded call data, outputs are only available for traces
+const FUNCTION_COLUMNS: [&str; 4] =
+    ["function_selector", "function_name", "function_inputs", "function_outputs"];
+
+fn with_columns(include_columns: &Option<Vec<String>>, columns: &[&str]) -> Option<Vec<String>> {
+    let mut include_columns = include_columns.clone().unwrap_or_default();
+    if include_columns != ["all"] {
+        include_columns.extend(columns.iter().map(|column| column.to_string()));
+    }
+    Some(include_columns)
+}
+
 fn parse_sort(
     raw_sort: &Option<Vec<String>>,
     datatypes: &Vec<Datatype>,
@@ -130,21 +418,141 @@ fn parse_sort(
     }
 }
 
-fn parse_address(input: &Option<String>) -> Option<ValueOrArray<H160>> {
-    input.as_ref().and_then(|data| {
-        <[u8; 20]>::from_hex(data.as_str().chars().skip(2).collect::<String>().as_str())
-            .ok()
-            .map(H160)
-            .map(ValueOrArray::Value)
-    })
+/// parse contract addresses, a single address is used as a value and several as an array
+fn parse_address(inputs: &Option<Vec<String>>) -> Result<Option<ValueOrArray<H160>>, ParseError> {
+    let addresses = addresses::parse_address_filter(inputs, "contract_address")?;
+    Ok(addresses.map(|mut addresses| match addresses.len() {
+        1 => ValueOrArray::Value(addresses.remove(0)),
+        _ => ValueOrArray::Array(addresses),
+    }))
+}
+
+/// parse storage slots, left padding short values to 32 bytes
+fn parse_slots(inputs: &[String]) -> Result<Vec<H256>, ParseError> {
+    let mut slots = Vec::new();
+    for input in inputs.iter() {
+        match input.strip_prefix("0x") {
+            Some(digits) if digits.len() <= 64 => {
+                let slot = hex::decode(format!("{:0>64}", digits))
+                    .map_err(|_e| ParseError::ParseError(format!("invalid slot: {}", input)))?;
+                slots.push(H256::from_slice(&slot));
+            }
+            Some(_) => return Err(ParseError::ParseError(format!("invalid slot: {}", input))),
+            None => {
+                for slot in addresses::parse_binary_inputs(&[input.clone()], "slot")? {
+                    if slot.len() > 32 {
+                        return Err(ParseError::ParseError(format!(
+                            "invalid slot: 0x{}",
+                            hex::encode(slot)
+                        )))
+                    }
+                    let mut padded = [0u8; 32];
+                    padded[32 - slot.len()..].copy_from_slice(&slot);
+                    slots.push(H256(padded));
+                }
+            }
+        }
+    }
+    Ok(slots)
+}
+
+/// parse opcode names, adding the names clients use for renamed opcodes
+fn parse_opcodes(inputs: &[String]) -> Result<Vec<String>, ParseError> {
+    let mut opcodes = Vec::new();
+    for input in inputs.iter() {
+        let opcode = match input.to_uppercase().as_str() {
+            "KECCAK256" => Ok(Opcode::SHA3),
+            name => name.parse::<Opcode>(),
+        }
+        .map_err(|_e| ParseError::ParseError(format!("invalid opcode: {}", input)))?;
+        opcodes.push(opcode.to_string());
+        match opcode {
+            Opcode::SHA3 => opcodes.push("KECCAK256".to_string()),
+            Opcode::DIFFICULTY => opcodes.push("PREVRANDAO".to_string()),
+            _ => {}
+        }
+    }
+    Ok(opcodes)
+}
+
+/// parse 4 byte function selectors of call data
+fn parse_function_selectors(inputs: &[String]) -> Result<Vec<[u8; 4]>, ParseError> {
+    let selectors = addresses::parse_binary_inputs(inputs, "function_selector")?
+        .into_iter()
+        .map(|selector| match selector.len() {
+            4 => Ok([selector[0], selector[1], selector[2], selector[3]]),
+            _ => Err(ParseError::ParseError(format!(
+                "invalid function selector: 0x{}",
+                hex::encode(selector)
+            ))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    match selectors.len() {
+        0 => Err(ParseError::ParseError(format!("no function selectors in {}", inputs.join(" ")))),
+        _ => Ok(selectors),
+    }
+}
+
+/// parse topics of a log filter, logs match if they have any of the given topics
+fn parse_topic(
+    inputs: &Option<Vec<String>>,
+    default_column: &str,
+) -> Result<Option<ValueOrArray<Option<H256>>>, ParseError> {
+    let inputs = match inputs {
+        Some(inputs) => inputs,
+        None => return Ok(None),
+    };
+    let mut topics = addresses::parse_binary_inputs(inputs, default_column)?
+        .into_iter()
+        .map(|topic| match topic.len() {
+            32 => Ok(Some(H256::from_slice(&topic))),
+            _ => Err(ParseError::ParseError(format!("invalid topic: 0x{}", hex::encode(topic)))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    match topics.len() {
+        0 => Err(ParseError::ParseError(format!("no topics in {}", inputs.join(" ")))),
+        1 => Ok(Some(ValueOrArray::Value(topics.remove(0)))),
+        _ => Ok(Some(ValueOrArray::Array(topics))),
+    }
 }
 
-fn parse_topic(input: &Option<String>) -> Option<ValueOrArray<Option<H256>>> {
-    let value = input.as_ref().and_then(|data| {
-        <[u8; 32]>::from_hex(data.as_str().chars().skip(2).collect::<String>().as_str())
-            .ok()
-            .map(H256)
-    });
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(inputs: &[&str]) -> Result<Vec<H256>, ParseError> {
+        parse_slots(&inputs.iter().map(|input| input.to_string()).collect::<Vec<_>>())
+    }
+
+    fn functions(inputs: &[&str]) -> Vec<Function> {
+        inputs
+            .iter()
+            .map(|signature| signatures::parse_function_signature(signature).unwrap())
+            .collect()
+    }
 
-    value.map(|inner| ValueOrArray::Value(Some(inner)))
+    #[test]
+    fn parse_slots_left_pads_short_values() {
+        assert_eq!(slot(&["0x0"]).ok(), Some(vec![H256::zero()]));
+        assert_eq!(slot(&["0x1"]).ok(), Some(vec![H256::from_low_u64_be(1)]));
+        assert_eq!(slot(&["0xabc"]).ok(), Some(vec![H256::from_low_u64_be(0xabc)]));
+        let full = format!("0x{}", "ff".repeat(32));
+        assert_eq!(slot(&[full.as_str()]).ok(), Some(vec![H256::repeat_byte(0xff)]));
+        let too_long = format!("0x{}", "ff".repeat(33));
+        assert!(slot(&[too_long.as_str()]).is_err());
+        assert!(slot(&["0xzz"]).is_err());
+    }
+
+    #[test]
+    fn clashing_selectors_are_rejected() {
+        let clashing = functions(&["burn(uint256)", "collate_propagate_storage(bytes16)"]);
+        assert_eq!(clashing[0].short_signature(), clashing[1].short_signature());
+        assert!(check_selector_clashes(&clashing).is_err());
+
+        let duplicated = functions(&["burn(uint256)", "function burn(uint256 amount)"]);
+        assert!(check_selector_clashes(&duplicated).is_ok());
+
+        let distinct = functions(&["burn(uint256)", "balanceOf(address)(uint256)"]);
+        assert!(check_selector_clashes(&distinct).is_ok());
+    }
 }
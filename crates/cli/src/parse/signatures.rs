@@ -0,0 +1,146 @@
+use std::{fs, path::Path};
+
+use ethers::abi::{
+    token::{LenientTokenizer, Tokenizer},
+    Abi, Event, Function, HumanReadableParser,
+};
+
+use cryo_freeze::ParseError;
+
+/// parse a function signature like `balanceOf(address)(uint256)`, a human readable form
+/// like `function balanceOf(address) returns (uint256)` is also accepted
+pub(crate) fn parse_function_signature(signature: &str) -> Result<Function, ParseError> {
+    let signature = signature.trim();
+    let human_readable = if signature.contains(" returns") {
+        signature.to_string()
+    } else {
+        // split inputs from outputs at the parenthesis closing the inputs
+        let mut depth = 0;
+        let mut inputs_end = None;
+        for (i, c) in signature.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        inputs_end = Some(i + 1);
+                        break
+                    }
+                }
+                _ => {}
+            }
+        }
+        match inputs_end {
+            Some(end) if end < signature.len() => {
+                format!("{} returns {}", &signature[..end], &signature[end..])
+            }
+            _ => signature.to_string(),
+        }
+    };
+    HumanReadableParser::parse_function(&human_readable)
+        .map_err(|_e| ParseError::ParseError(format!("invalid function signature: {}", signature)))
+}
+
+/// encode call data of a function call from string arguments
+pub(crate) fn encode_function_call(
+    function: &Function,
+    inputs: &[String],
+) -> Result<Vec<u8>, ParseError> {
+    if inputs.len() != function.inputs.len() {
+        return Err(ParseError::ParseError(format!(
+            "{} expects {} inputs, got {}",
+            function.name,
+            function.inputs.len(),
+            inputs.len()
+        )))
+    }
+    let tokens = function
+        .inputs
+        .iter()
+        .zip(inputs)
+        .map(|(param, input)| {
+            LenientTokenizer::tokenize(&param.kind, input)
+                .map_err(|_e| ParseError::ParseError(format!("invalid input: {}", input)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    function
+        .encode_input(&tokens)
+        .map_err(|_e| ParseError::ParseError("could not encode function inputs".to_string()))
+}
+
+/// parse an event signature like `Transfer(address indexed from, address indexed to, uint256)`
+pub(crate) fn parse_event_signature(signature: &str) -> Result<Event, ParseError> {
+    let event = HumanReadableParser::parse_event(signature.trim())
+        .map_err(|_e| ParseError::ParseError(format!("invalid event signature: {}", signature)))?;
+    if event.anonymous {
+        return Err(ParseError::ParseError(format!(
+            "anonymous events cannot be decoded: {}",
+            signature
+        )))
+    }
+    Ok(event)
+}
+
+/// load abis from json files, directories are searched for json files
+pub(crate) fn load_abis(paths: &[String]) -> Result<Vec<Abi>, ParseError> {
+    let mut abis = Vec::new();
+    for path in paths.iter() {
+        let path = Path::new(path);
+        if path.is_dir() {
+            let entries = fs::read_dir(path).map_err(|_e| {
+                ParseError::ParseError(format!("could not read directory: {}", path.display()))
+            })?;
+            let mut files: Vec<_> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().map(|ext| ext == "json").unwrap_or(false))
+                .collect();
+            files.sort();
+            for file in files.iter() {
+                abis.push(load_abi(file)?);
+            }
+        } else {
+            abis.push(load_abi(path)?);
+        }
+    }
+    Ok(abis)
+}
+
+fn load_abi(path: &Path) -> Result<Abi, ParseError> {
+    let file = fs::File::open(path)
+        .map_err(|_e| ParseError::ParseError(format!("could not open abi: {}", path.display())))?;
+    Abi::load(file).map_err(|_e| ParseError::ParseError(format!("invalid abi: {}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::ParamType;
+
+    use super::*;
+
+    fn kinds(params: &[ethers::abi::Param]) -> Vec<ParamType> {
+        params.iter().map(|param| param.kind.clone()).collect()
+    }
+
+    #[test]
+    fn function_signatures_split_inputs_from_outputs() {
+        let function = parse_function_signature("balanceOf(address)(uint256)").unwrap();
+        assert_eq!(function.name, "balanceOf");
+        assert_eq!(kinds(&function.inputs), vec![ParamType::Address]);
+        assert_eq!(kinds(&function.outputs), vec![ParamType::Uint(256)]);
+
+        let function = parse_function_signature("f((uint8,bool),bytes)((address,uint256)[])");
+        let function = function.unwrap();
+        let input = ParamType::Tuple(vec![ParamType::Uint(8), ParamType::Bool]);
+        assert_eq!(kinds(&function.inputs), vec![input, ParamType::Bytes]);
+        let output = ParamType::Tuple(vec![ParamType::Address, ParamType::Uint(256)]);
+        assert_eq!(kinds(&function.outputs), vec![ParamType::Array(Box::new(output))]);
+
+        let function = parse_function_signature("totalSupply()").unwrap();
+        assert!(function.inputs.is_empty() && function.outputs.is_empty());
+
+        let function = parse_function_signature("function name() returns (string)").unwrap();
+        assert_eq!(kinds(&function.outputs), vec![ParamType::String]);
+
+        assert!(parse_function_signature("f(uint256").is_err());
+    }
+}
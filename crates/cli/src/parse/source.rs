@@ -1,11 +1,11 @@
-use std::env;
+use std::{env, sync::atomic::AtomicBool};
 
 use ethers::prelude::*;
 use governor::{Quota, RateLimiter};
 use polars::prelude::*;
 use std::num::NonZeroU32;
 
-use cryo_freeze::{ParseError, Source};
+use cryo_freeze::{ParseError, Source, TraceBackend};
 
 use crate::args::Args;
 
@@ -20,6 +20,8 @@ pub(crate) async fn parse_source(args: &Args) -> Result<Source, ParseError> {
         .map_err(|_e| ParseError::ParseError("could not connect to provider".to_string()))?
         .as_u64();
 
+    let trace_backend = parse_trace_backend(args, &provider).await?;
+
     let rate_limiter = match args.requests_per_second {
         Some(rate_limit) => match NonZeroU32::new(rate_limit) {
             Some(value) => {
@@ -45,11 +47,33 @@ pub(crate) async fn parse_source(args: &Args) -> Result<Source, ParseError> {
         rate_limiter,
         inner_request_size: args.inner_request_size,
         max_concurrent_chunks,
+        trace_backend,
+        block_receipts_unsupported: Arc::new(AtomicBool::new(false)),
     };
 
     Ok(output)
 }
 
+async fn parse_trace_backend(
+    args: &Args,
+    provider: &Provider<Http>,
+) -> Result<TraceBackend, ParseError> {
+    match args.trace_backend.as_deref() {
+        Some("parity") => Ok(TraceBackend::Parity),
+        Some("geth") => Ok(TraceBackend::Geth),
+        Some(backend) => Err(ParseError::ParseError(format!("invalid trace backend: {}", backend))),
+        None => {
+            // geth does not serve the trace_ namespace, other clients do
+            let client_version = provider.client_version().await.unwrap_or_default();
+            if client_version.to_lowercase().starts_with("geth") {
+                Ok(TraceBackend::Geth)
+            } else {
+                Ok(TraceBackend::Parity)
+            }
+        }
+    }
+}
+
 fn parse_rpc_url(args: &Args) -> String {
     let mut url = match &args.rpc {
         Some(url) => url.clone(),
@@ -6,7 +6,8 @@ use std::time::SystemTime;
 use thousands::Separable;
 
 use cryo_freeze::{
-    BlockChunk, Chunk, ChunkData, Datatype, FileOutput, FreezeSummary, MultiQuery, Source, Table,
+    AddressChunk, BlockChunk, Chunk, ChunkData, Datatype, FileOutput, FreezeSummary, MultiQuery,
+    Source, Table, TransactionChunk,
 };
 
 const TITLE_R: u8 = 0;
@@ -43,7 +44,29 @@ pub(crate) fn print_cryo_summary(query: &MultiQuery, source: &Source, sink: &Fil
             _ => None,
         })
         .collect();
-    print_block_chunks(block_chunks);
+    let address_chunks: Vec<(AddressChunk, BlockChunk)> = query
+        .chunks
+        .iter()
+        .filter_map(|x| match x.clone() {
+            Chunk::Address(chunk, blocks) => Some((chunk, blocks)),
+            _ => None,
+        })
+        .collect();
+    let transaction_chunks: Vec<TransactionChunk> = query
+        .chunks
+        .iter()
+        .filter_map(|x| match x.clone() {
+            Chunk::Transaction(chunk) => Some(chunk),
+            _ => None,
+        })
+        .collect();
+    if !address_chunks.is_empty() {
+        print_address_chunks(address_chunks);
+    } else if !transaction_chunks.is_empty() {
+        print_transaction_chunks(transaction_chunks);
+    } else {
+        print_block_chunks(block_chunks);
+    }
     print_bullet("max concurrent chunks", source.max_concurrent_chunks.separate_with_commas());
     if query.schemas.contains_key(&Datatype::Logs) {
         print_bullet("inner request size", source.inner_request_size.to_string());
@@ -69,6 +92,32 @@ fn print_block_chunks(chunks: Vec<BlockChunk>) {
     print_bullet("total block chunks", chunks.len().separate_with_commas());
 }
 
+fn print_address_chunks(chunks: Vec<(AddressChunk, BlockChunk)>) {
+    let block_chunks: Vec<BlockChunk> = chunks.iter().map(|(_, blocks)| blocks.clone()).collect();
+    if let Some(min) = block_chunks.min_value() {
+        print_bullet("min block", min.separate_with_commas());
+    };
+    if let Some(max) = block_chunks.max_value() {
+        print_bullet("max block", max.separate_with_commas());
+    };
+    let n_pairs: u64 = chunks.iter().map(|(chunk, blocks)| chunk.size() * blocks.size()).sum();
+    print_bullet("total address-block pairs", n_pairs.separate_with_commas());
+    if let Some((first_chunk, first_blocks)) = chunks.get(0) {
+        let chunk_size = first_chunk.size() * first_blocks.size();
+        print_bullet("address chunk size", chunk_size.separate_with_commas());
+    };
+    print_bullet("total address chunks", chunks.len().separate_with_commas());
+}
+
+fn print_transaction_chunks(chunks: Vec<TransactionChunk>) {
+    print_bullet("total transactions", chunks.size().separate_with_commas());
+    if let Some(first_chunk) = chunks.get(0) {
+        let chunk_size = first_chunk.size();
+        print_bullet("transaction chunk size", chunk_size.separate_with_commas());
+    };
+    print_bullet("total transaction chunks", chunks.len().separate_with_commas());
+}
+
 fn print_schemas(schemas: &HashMap<Datatype, Table>) {
     schemas.iter().for_each(|(name, schema)| {
         println!();
@@ -137,13 +186,16 @@ pub(crate) fn print_cryo_conclusion(
             _ => None,
         })
         .collect();
-    let n_chunks = block_chunks.len();
+    let n_chunks = query.chunks.len();
     print_bullet("chunks errored", freeze_summary.n_errored.separate_with_commas());
     print_bullet("chunks skipped", freeze_summary.n_skipped.separate_with_commas());
     print_bullet(
         "chunks collected",
         format!("{} / {}", freeze_summary.n_completed.separate_with_commas(), n_chunks),
     );
+    if block_chunks.is_empty() {
+        return
+    }
     let total_blocks = block_chunks.size() as f64;
     let blocks_completed =
         total_blocks * (freeze_summary.n_completed as f64 / block_chunks.len() as f64);
@@ -0,0 +1,224 @@
+use std::collections::{HashMap, HashSet};
+
+use ethers::prelude::*;
+use polars::prelude::*;
+use tokio::sync::mpsc;
+
+use super::{blocks, blocks_and_transactions, logs, traces};
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        AddressAppearances, BlockChunk, CollectError, ColumnType, Dataset, Datatype, RowFilter,
+        Source, Table,
+    },
+    with_series, with_series_binary,
+};
+
+#[async_trait::async_trait]
+impl Dataset for AddressAppearances {
+    fn datatype(&self) -> Datatype {
+        Datatype::AddressAppearances
+    }
+
+    fn name(&self) -> &'static str {
+        "address_appearances"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("transaction_index", ColumnType::UInt32),
+            ("transaction_hash", ColumnType::Binary),
+            ("address", ColumnType::Binary),
+            ("role", ColumnType::String),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec!["block_number", "transaction_index", "transaction_hash", "address", "role"]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["block_number".to_string(), "transaction_index".to_string()]
+    }
+
+    async fn collect_block_chunk(
+        &self,
+        chunk: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        _filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let blocks_rx =
+            blocks_and_transactions::fetch_blocks_and_transactions(chunk, source, false).await;
+        let traces_rx = traces::fetch_traces(chunk, source).await;
+        let logs_rx = logs::fetch_logs(chunk, source, None).await;
+
+        let mut columns = AppearanceColumns::default();
+        add_transactions(blocks_rx, &mut columns).await?;
+        add_traces(traces_rx, &mut columns).await?;
+        add_logs(logs_rx, &mut columns).await?;
+        columns.create_df(schema, source.chain_id)
+    }
+}
+
+/// distinct (block, transaction, address, role) appearances
+#[derive(Default)]
+struct AppearanceColumns {
+    seen: HashSet<(u64, Option<H256>, H160, &'static str)>,
+    block_number: Vec<u32>,
+    transaction_index: Vec<Option<u32>>,
+    transaction_hash: Vec<Option<Vec<u8>>>,
+    address: Vec<Vec<u8>>,
+    role: Vec<String>,
+}
+
+impl AppearanceColumns {
+    fn add(
+        &mut self,
+        block_number: u64,
+        transaction_index: Option<u64>,
+        transaction_hash: Option<H256>,
+        address: H160,
+        role: &'static str,
+    ) {
+        if !self.seen.insert((block_number, transaction_hash, address, role)) {
+            return
+        }
+        self.block_number.push(block_number as u32);
+        self.transaction_index.push(transaction_index.map(|index| index as u32));
+        self.transaction_hash.push(transaction_hash.map(|hash| hash.as_bytes().to_vec()));
+        self.address.push(address.as_bytes().to_vec());
+        self.role.push(role.to_string());
+    }
+
+    fn create_df(self, schema: &Table, chain_id: u64) -> Result<DataFrame, CollectError> {
+        let n_rows = self.address.len();
+        let mut cols = Vec::new();
+        with_series!(cols, "block_number", self.block_number, schema);
+        with_series!(cols, "transaction_index", self.transaction_index, schema);
+        with_series_binary!(cols, "transaction_hash", self.transaction_hash, schema);
+        with_series_binary!(cols, "address", self.address, schema);
+        with_series!(cols, "role", self.role, schema);
+
+        if schema.has_column("chain_id") {
+            cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+        }
+
+        DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+    }
+}
+
+async fn add_transactions(
+    mut rx: mpsc::Receiver<blocks::BlockTxGasTuple<Transaction>>,
+    columns: &mut AppearanceColumns,
+) -> Result<(), CollectError> {
+    while let Some(message) = rx.recv().await {
+        let (block, _) = message?;
+        for tx in block.transactions.iter() {
+            let (number, index) = match (tx.block_number, tx.transaction_index) {
+                (Some(number), Some(index)) => (number.as_u64(), Some(index.as_u64())),
+                _ => continue,
+            };
+            columns.add(number, index, Some(tx.hash), tx.from, "tx_from");
+            if let Some(to) = tx.to {
+                columns.add(number, index, Some(tx.hash), to, "tx_to");
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn add_traces(
+    mut rx: mpsc::Receiver<Result<Vec<Trace>, CollectError>>,
+    columns: &mut AppearanceColumns,
+) -> Result<(), CollectError> {
+    while let Some(message) = rx.recv().await {
+        let block_traces = message?;
+        for trace in block_traces.iter() {
+            let number = trace.block_number;
+            let index = trace.transaction_position.map(|index| index as u64);
+            let hash = trace.transaction_hash;
+            match &trace.action {
+                Action::Call(action) => {
+                    columns.add(number, index, hash, action.from, "trace_from");
+                    columns.add(number, index, hash, action.to, "trace_to");
+                }
+                Action::Create(action) => {
+                    columns.add(number, index, hash, action.from, "trace_from");
+                }
+                Action::Suicide(action) => {
+                    columns.add(number, index, hash, action.address, "trace_from");
+                    columns.add(number, index, hash, action.refund_address, "trace_to");
+                }
+                Action::Reward(action) => {
+                    columns.add(number, index, hash, action.author, "trace_to");
+                }
+            }
+            if let Some(Res::Create(result)) = &trace.result {
+                columns.add(number, index, hash, result.address, "contract_created");
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn add_logs(
+    mut rx: mpsc::Receiver<Result<Vec<Log>, CollectError>>,
+    columns: &mut AppearanceColumns,
+) -> Result<(), CollectError> {
+    while let Some(message) = rx.recv().await {
+        for log in message?.iter() {
+            if let Some(true) = log.removed {
+                continue
+            }
+            let number = match log.block_number {
+                Some(number) => number.as_u64(),
+                None => continue,
+            };
+            let index = log.transaction_index.map(|index| index.as_u64());
+            let hash = log.transaction_hash;
+            columns.add(number, index, hash, log.address, "log_emitter");
+            for topic in log.topics.iter().skip(1) {
+                if let Some(address) = topic_address(topic) {
+                    columns.add(number, index, hash, address, "log_topic");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// address held by a topic, topics must be left padded with 12 zero bytes
+///
+/// small values such as token ids, amounts and the zero address are skipped by also requiring
+/// one of the first 4 address bytes to be non-zero
+fn topic_address(topic: &H256) -> Option<H160> {
+    let bytes = topic.as_bytes();
+    let padded = bytes[..12].iter().all(|b| *b == 0);
+    let small = bytes[12..16].iter().all(|b| *b == 0);
+    match padded && !small {
+        true => Some(H160::from_slice(&bytes[12..])),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topic_address_requires_padded_address() {
+        let address: H160 = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045".parse().unwrap();
+        assert_eq!(topic_address(&H256::from(address)), Some(address));
+
+        let token_id = H256::from_low_u64_be(1234);
+        assert_eq!(topic_address(&token_id), None);
+
+        assert_eq!(topic_address(&H256::zero()), None);
+
+        let hash = H256::repeat_byte(0xab);
+        assert_eq!(topic_address(&hash), None);
+    }
+}
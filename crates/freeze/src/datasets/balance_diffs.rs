@@ -4,7 +4,8 @@ use polars::prelude::*;
 
 use super::state_diffs;
 use crate::types::{
-    BalanceDiffs, BlockChunk, CollectError, ColumnType, Dataset, Datatype, RowFilter, Source, Table,
+    BalanceDiffs, BlockChunk, Chunk, CollectError, ColumnType, Dataset, Datatype, RowFilter,
+    Source, Table, TransactionChunk,
 };
 
 #[async_trait::async_trait]
@@ -51,6 +52,18 @@ impl Dataset for BalanceDiffs {
         schema: &Table,
         filter: Option<&RowFilter>,
     ) -> Result<DataFrame, CollectError> {
-        state_diffs::collect_single(&Datatype::BalanceDiffs, chunk, source, schema, filter).await
+        let chunk = Chunk::Block(chunk.clone());
+        state_diffs::collect_single(&Datatype::BalanceDiffs, &chunk, source, schema, filter).await
+    }
+
+    async fn collect_transaction_chunk(
+        &self,
+        chunk: &TransactionChunk,
+        source: &Source,
+        schema: &Table,
+        filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let chunk = Chunk::Transaction(chunk.clone());
+        state_diffs::collect_single(&Datatype::BalanceDiffs, &chunk, source, schema, filter).await
     }
 }
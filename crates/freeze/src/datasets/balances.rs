@@ -0,0 +1,172 @@
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+use ethers::prelude::*;
+use polars::prelude::*;
+use tokio::{sync::mpsc, task};
+
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        conversions::{u256_to_bytes, ToVecHex},
+        AddressChunk, Balances, BlockChunk, CollectError, ColumnType, Dataset, Datatype, RowFilter,
+        Source, Table,
+    },
+    with_series, with_series_binary,
+};
+
+#[async_trait::async_trait]
+impl Dataset for Balances {
+    fn datatype(&self) -> Datatype {
+        Datatype::Balances
+    }
+
+    fn name(&self) -> &'static str {
+        "balances"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("address", ColumnType::Binary),
+            ("balance", ColumnType::Binary),
+            ("balance_string", ColumnType::String),
+            ("balance_float", ColumnType::Float64),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec!["block_number", "address", "balance"]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["address".to_string(), "block_number".to_string()]
+    }
+
+    async fn collect_address_chunk(
+        &self,
+        chunk: &AddressChunk,
+        blocks: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        _filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let pairs = address_block_pairs(chunk, blocks)?;
+        let rx = fetch_address_block_values(pairs, source, |provider, address, block| async move {
+            provider.get_balance(address, Some(block)).await
+        })
+        .await;
+        balances_to_df(rx, schema, source.chain_id).await
+    }
+}
+
+/// get every (address, block number) pair of an address chunk and a block chunk
+pub(crate) fn address_block_pairs(
+    chunk: &AddressChunk,
+    blocks: &BlockChunk,
+) -> Result<Vec<(H160, u64)>, CollectError> {
+    let addresses = match chunk {
+        AddressChunk::Values(values) => values,
+        AddressChunk::Range(_, _) => {
+            return Err(CollectError::CollectError(
+                "address ranges cannot be collected, specify addresses explicitly".to_string(),
+            ))
+        }
+    };
+    let block_numbers = blocks.numbers();
+
+    let mut pairs = Vec::new();
+    for address in addresses.iter() {
+        if address.len() != 20 {
+            return Err(CollectError::CollectError("invalid address length".to_string()))
+        }
+        let address = H160::from_slice(address);
+        for number in block_numbers.iter() {
+            pairs.push((address, *number));
+        }
+    }
+    Ok(pairs)
+}
+
+/// fetch a value for every (address, block number) pair, using one request per pair
+pub(crate) async fn fetch_address_block_values<T, F, Fut>(
+    pairs: Vec<(H160, u64)>,
+    source: &Source,
+    request: F,
+) -> mpsc::Receiver<Result<(H160, u64, T), CollectError>>
+where
+    T: Send + 'static,
+    F: Fn(Arc<Provider<Http>>, H160, BlockId) -> Fut + Copy + Send + 'static,
+    Fut: Future<Output = Result<T, ProviderError>> + Send,
+{
+    let (tx, rx) = mpsc::channel(pairs.len().max(1));
+    let source = Arc::new(source.clone());
+
+    for (address, number) in pairs {
+        let tx = tx.clone();
+        let source = Arc::clone(&source);
+        task::spawn(async move {
+            let permit = match source.semaphore.clone() {
+                Some(semaphore) => Some(semaphore.acquire_owned().await),
+                _ => None,
+            };
+            if let Some(limiter) = source.rate_limiter.as_ref() {
+                Arc::clone(limiter).until_ready().await;
+            }
+            let block = BlockId::Number(BlockNumber::Number(number.into()));
+            let result = request(Arc::clone(&source.provider), address, block)
+                .await
+                .map(|value| (address, number, value))
+                .map_err(CollectError::ProviderError);
+            drop(permit);
+            match tx.send(result).await {
+                Ok(_) => {}
+                Err(tokio::sync::mpsc::error::SendError(_e)) => {
+                    eprintln!("send error, try using a rate limit with --requests-per-second or limiting max concurrency with --max-concurrent-requests");
+                    std::process::exit(1)
+                }
+            }
+        });
+    }
+    rx
+}
+
+async fn balances_to_df(
+    mut rx: mpsc::Receiver<Result<(H160, u64, U256), CollectError>>,
+    schema: &Table,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut address: Vec<Vec<u8>> = Vec::new();
+    let mut balance: Vec<Vec<u8>> = Vec::new();
+    let mut balance_string: Vec<String> = Vec::new();
+    let mut balance_float: Vec<Option<f64>> = Vec::new();
+
+    let mut n_rows = 0;
+    while let Some(message) = rx.recv().await {
+        match message {
+            Ok((addr, number, value)) => {
+                n_rows += 1;
+                block_number.push(number as u32);
+                address.push(addr.as_bytes().to_vec());
+                balance.push(u256_to_bytes(&value));
+                balance_string.push(value.to_string());
+                balance_float.push(value.to_string().parse::<f64>().ok());
+            }
+            Err(e) => return Err(CollectError::RPCError(e.to_string())),
+        }
+    }
+
+    let mut cols = Vec::new();
+    with_series!(cols, "block_number", block_number, schema);
+    with_series_binary!(cols, "address", address, schema);
+    with_series_binary!(cols, "balance", balance, schema);
+    with_series!(cols, "balance_string", balance_string, schema);
+    with_series!(cols, "balance_float", balance_float, schema);
+
+    if schema.has_column("chain_id") {
+        cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+    }
+
+    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+}
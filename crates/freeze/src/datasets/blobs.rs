@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use ethers::prelude::*;
+use polars::prelude::*;
+use tokio::sync::mpsc;
+
+use super::{blocks, blocks_and_transactions};
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        conversions::ToVecHex, Blobs, BlockChunk, CollectError, ColumnType, Dataset, Datatype,
+        RowFilter, Source, Table,
+    },
+    with_series, with_series_binary,
+};
+
+#[async_trait::async_trait]
+impl Dataset for Blobs {
+    fn datatype(&self) -> Datatype {
+        Datatype::Blobs
+    }
+
+    fn name(&self) -> &'static str {
+        "blobs"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("transaction_index", ColumnType::UInt32),
+            ("transaction_hash", ColumnType::Binary),
+            ("blob_index", ColumnType::UInt32),
+            ("versioned_hash", ColumnType::Binary),
+            ("max_fee_per_blob_gas", ColumnType::UInt64),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec![
+            "block_number",
+            "transaction_index",
+            "transaction_hash",
+            "blob_index",
+            "versioned_hash",
+            "max_fee_per_blob_gas",
+        ]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["block_number".to_string(), "transaction_index".to_string(), "blob_index".to_string()]
+    }
+
+    async fn collect_block_chunk(
+        &self,
+        chunk: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        _filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let rx = blocks_and_transactions::fetch_blocks_and_transactions(chunk, source, false).await;
+        blobs_to_df(rx, schema, source.chain_id).await
+    }
+}
+
+async fn blobs_to_df(
+    mut rx: mpsc::Receiver<blocks::BlockTxGasTuple<Transaction>>,
+    schema: &Table,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut transaction_index: Vec<u32> = Vec::new();
+    let mut transaction_hash: Vec<Vec<u8>> = Vec::new();
+    let mut blob_index: Vec<u32> = Vec::new();
+    let mut versioned_hash: Vec<Vec<u8>> = Vec::new();
+    let mut max_fee_per_blob_gas: Vec<Option<u64>> = Vec::new();
+
+    let mut n_rows = 0;
+    while let Some(message) = rx.recv().await {
+        match message {
+            Ok((block, _)) => {
+                let number = match block.number {
+                    Some(number) => number.as_u32(),
+                    None => return Err(CollectError::CollectError("block has no number".into())),
+                };
+                for tx in block.transactions.iter() {
+                    let max_fee = blocks::other_u64(&tx.other, "maxFeePerBlobGas");
+                    for (index, hash) in blocks::blob_versioned_hashes(tx).iter().enumerate() {
+                        n_rows += 1;
+                        block_number.push(number);
+                        transaction_index
+                            .push(tx.transaction_index.map(|x| x.as_u32()).unwrap_or_default());
+                        transaction_hash.push(tx.hash.as_bytes().to_vec());
+                        blob_index.push(index as u32);
+                        versioned_hash.push(hash.as_bytes().to_vec());
+                        max_fee_per_blob_gas.push(max_fee);
+                    }
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    let mut cols = Vec::new();
+    with_series!(cols, "block_number", block_number, schema);
+    with_series!(cols, "transaction_index", transaction_index, schema);
+    with_series_binary!(cols, "transaction_hash", transaction_hash, schema);
+    with_series!(cols, "blob_index", blob_index, schema);
+    with_series_binary!(cols, "versioned_hash", versioned_hash, schema);
+    with_series!(cols, "max_fee_per_blob_gas", max_fee_per_blob_gas, schema);
+
+    if schema.has_column("chain_id") {
+        cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+    }
+
+    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+}
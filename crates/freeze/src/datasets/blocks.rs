@@ -1,16 +1,16 @@
 use std::{collections::HashMap, sync::Arc};
 
-use ethers::prelude::*;
+use ethers::{abi::Function, prelude::*};
 use polars::prelude::*;
 use tokio::{sync::mpsc, task};
 
 use crate::{
     dataframes::SortableDataFrame,
     types::{
-        conversions::{ToVecHex, ToVecU8},
+        conversions::{self, ToVecHex, ToVecU8},
         BlockChunk, Blocks, CollectError, ColumnType, Dataset, Datatype, RowFilter, Source, Table,
     },
-    with_series, with_series_binary,
+    with_series, with_series_binary, with_series_binary_list,
 };
 
 pub(crate) type BlockTxGasTuple<TX> = Result<(Block<TX>, Option<Vec<u32>>), CollectError>;
@@ -41,8 +41,15 @@ impl Dataset for Blocks {
             ("total_difficulty", ColumnType::String),
             ("size", ColumnType::UInt32),
             ("base_fee_per_gas", ColumnType::UInt64),
+            ("withdrawals_root", ColumnType::Binary),
+            ("uncles_hash", ColumnType::Binary),
+            ("uncle_count", ColumnType::UInt32),
+            ("blob_gas_used", ColumnType::UInt64),
+            ("excess_blob_gas", ColumnType::UInt64),
+            ("parent_beacon_block_root", ColumnType::Binary),
             ("chain_id", ColumnType::UInt64),
-            // not including: transactions, seal_fields, epoch_snark_data, randomness
+            // not including: transactions, seal_fields, epoch_snark_data, randomness, withdrawals,
+            // uncles
         ])
     }
 
@@ -62,7 +69,7 @@ impl Dataset for Blocks {
         _filter: Option<&RowFilter>,
     ) -> Result<DataFrame, CollectError> {
         let rx = fetch_blocks(chunk, source).await;
-        let output = blocks_to_dfs(rx, &Some(schema), &None, source.chain_id).await;
+        let output = blocks_to_dfs(rx, &Some(schema), &None, None, source.chain_id).await;
         match output {
             Ok((Some(blocks_df), _)) => Ok(blocks_df),
             Ok((None, _)) => Err(CollectError::BadSchemaError),
@@ -71,7 +78,7 @@ impl Dataset for Blocks {
     }
 }
 
-async fn fetch_blocks(
+pub(crate) async fn fetch_blocks(
     block_chunk: &BlockChunk,
     source: &Source,
 ) -> mpsc::Receiver<BlockTxGasTuple<TxHash>> {
@@ -109,18 +116,84 @@ async fn fetch_blocks(
 }
 
 pub(crate) trait ProcessTransactions {
-    fn process(&self, schema: &Table, columns: &mut TransactionColumns, gas_used: Option<u32>);
+    /// add transaction to columns, returns whether it passed the filter
+    fn process(
+        &self,
+        schema: &Table,
+        columns: &mut TransactionColumns,
+        gas_used: Option<u32>,
+        filter: &TransactionFilter,
+    ) -> bool;
 }
 
 impl ProcessTransactions for TxHash {
-    fn process(&self, _schema: &Table, _columns: &mut TransactionColumns, _gas_used: Option<u32>) {
+    fn process(
+        &self,
+        _schema: &Table,
+        _columns: &mut TransactionColumns,
+        _gas_used: Option<u32>,
+        _filter: &TransactionFilter,
+    ) -> bool {
         panic!("transaction data not available to process")
     }
 }
 
 impl ProcessTransactions for Transaction {
-    fn process(&self, schema: &Table, columns: &mut TransactionColumns, gas_used: Option<u32>) {
-        process_transaction(self, schema, columns, gas_used)
+    fn process(
+        &self,
+        schema: &Table,
+        columns: &mut TransactionColumns,
+        gas_used: Option<u32>,
+        filter: &TransactionFilter,
+    ) -> bool {
+        process_transaction(self, schema, columns, gas_used, filter)
+    }
+}
+
+/// options for decoding and filtering transactions
+#[derive(Default)]
+pub(crate) struct TransactionFilter {
+    functions: HashMap<[u8; 4], Function>,
+    from_address: Option<Vec<H160>>,
+    to_address: Option<Vec<H160>>,
+    function_selectors: Option<Vec<[u8; 4]>>,
+}
+
+impl TransactionFilter {
+    pub(crate) fn new(filter: Option<&RowFilter>) -> Self {
+        match filter {
+            Some(filter) => Self {
+                functions: conversions::function_selectors(
+                    filter.functions.as_deref().unwrap_or_default(),
+                ),
+                from_address: filter.from_address.clone(),
+                to_address: filter.to_address.clone(),
+                function_selectors: filter.function_selectors.clone(),
+            },
+            None => Self::default(),
+        }
+    }
+
+    /// whether transaction matches every given filter
+    fn matches(&self, tx: &Transaction) -> bool {
+        if let Some(from_address) = &self.from_address {
+            if !from_address.contains(&tx.from) {
+                return false
+            }
+        }
+        if let Some(to_address) = &self.to_address {
+            match tx.to {
+                Some(to) if to_address.contains(&to) => {}
+                _ => return false,
+            }
+        }
+        if let Some(function_selectors) = &self.function_selectors {
+            match (tx.to, conversions::call_selector(&tx.input)) {
+                (Some(_), Some(selector)) if function_selectors.contains(&selector) => {}
+                _ => return false,
+            }
+        }
+        true
     }
 }
 
@@ -128,9 +201,11 @@ pub(crate) async fn blocks_to_dfs<TX: ProcessTransactions>(
     mut blocks: mpsc::Receiver<BlockTxGasTuple<TX>>,
     blocks_schema: &Option<&Table>,
     transactions_schema: &Option<&Table>,
+    filter: Option<&RowFilter>,
     chain_id: u64,
 ) -> Result<(Option<DataFrame>, Option<DataFrame>), CollectError> {
     // initialize
+    let filter = TransactionFilter::new(filter);
     let mut block_columns =
         if blocks_schema.is_none() { BlockColumns::new(0) } else { BlockColumns::new(100) };
     let mut transaction_columns = if transactions_schema.is_none() {
@@ -153,14 +228,21 @@ pub(crate) async fn blocks_to_dfs<TX: ProcessTransactions>(
                     match gas_used {
                         Some(gas_used) => {
                             for (tx, gas_used) in block.transactions.iter().zip(gas_used) {
-                                n_txs += 1;
-                                tx.process(schema, &mut transaction_columns, Some(gas_used))
+                                if tx.process(
+                                    schema,
+                                    &mut transaction_columns,
+                                    Some(gas_used),
+                                    &filter,
+                                ) {
+                                    n_txs += 1;
+                                }
                             }
                         }
                         None => {
                             for tx in block.transactions.iter() {
-                                n_txs += 1;
-                                tx.process(schema, &mut transaction_columns, None)
+                                if tx.process(schema, &mut transaction_columns, None, &filter) {
+                                    n_txs += 1;
+                                }
                             }
                         }
                     }
@@ -200,6 +282,12 @@ struct BlockColumns {
     total_difficulty: Vec<Option<Vec<u8>>>,
     size: Vec<Option<u32>>,
     base_fee_per_gas: Vec<Option<u64>>,
+    withdrawals_root: Vec<Option<Vec<u8>>>,
+    uncles_hash: Vec<Vec<u8>>,
+    uncle_count: Vec<u32>,
+    blob_gas_used: Vec<Option<u64>>,
+    excess_blob_gas: Vec<Option<u64>>,
+    parent_beacon_block_root: Vec<Option<Vec<u8>>>,
 }
 
 impl BlockColumns {
@@ -219,6 +307,12 @@ impl BlockColumns {
             total_difficulty: Vec::with_capacity(n),
             size: Vec::with_capacity(n),
             base_fee_per_gas: Vec::with_capacity(n),
+            withdrawals_root: Vec::with_capacity(n),
+            uncles_hash: Vec::with_capacity(n),
+            uncle_count: Vec::with_capacity(n),
+            blob_gas_used: Vec::with_capacity(n),
+            excess_blob_gas: Vec::with_capacity(n),
+            parent_beacon_block_root: Vec::with_capacity(n),
         }
     }
 
@@ -243,6 +337,17 @@ impl BlockColumns {
         with_series_binary!(cols, "total_difficulty", self.total_difficulty, schema);
         with_series!(cols, "size", self.size, schema);
         with_series!(cols, "base_fee_per_gas", self.base_fee_per_gas, schema);
+        with_series_binary!(cols, "withdrawals_root", self.withdrawals_root, schema);
+        with_series_binary!(cols, "uncles_hash", self.uncles_hash, schema);
+        with_series!(cols, "uncle_count", self.uncle_count, schema);
+        with_series!(cols, "blob_gas_used", self.blob_gas_used, schema);
+        with_series!(cols, "excess_blob_gas", self.excess_blob_gas, schema);
+        with_series_binary!(
+            cols,
+            "parent_beacon_block_root",
+            self.parent_beacon_block_root,
+            schema
+        );
 
         if schema.has_column("chain_id") {
             cols.push(Series::new("chain_id", vec![chain_id; n_rows as usize]));
@@ -267,10 +372,15 @@ pub(crate) struct TransactionColumns {
     transaction_type: Vec<Option<u32>>,
     max_priority_fee_per_gas: Vec<Option<u64>>,
     max_fee_per_gas: Vec<Option<u64>>,
+    max_fee_per_blob_gas: Vec<Option<u64>>,
+    blob_versioned_hashes: Vec<Vec<Vec<u8>>>,
+    function_selector: Vec<Option<Vec<u8>>>,
+    function_name: Vec<Option<String>>,
+    function_inputs: Vec<Option<String>>,
 }
 
 impl TransactionColumns {
-    fn new(n: usize) -> Self {
+    pub(crate) fn new(n: usize) -> Self {
         Self {
             block_number: Vec::with_capacity(n),
             transaction_index: Vec::with_capacity(n),
@@ -286,10 +396,15 @@ impl TransactionColumns {
             transaction_type: Vec::with_capacity(n),
             max_priority_fee_per_gas: Vec::with_capacity(n),
             max_fee_per_gas: Vec::with_capacity(n),
+            max_fee_per_blob_gas: Vec::with_capacity(n),
+            blob_versioned_hashes: Vec::with_capacity(n),
+            function_selector: Vec::with_capacity(n),
+            function_name: Vec::with_capacity(n),
+            function_inputs: Vec::with_capacity(n),
         }
     }
 
-    fn create_df(
+    pub(crate) fn create_df(
         self,
         schema: &Table,
         chain_id: u64,
@@ -310,6 +425,11 @@ impl TransactionColumns {
         with_series!(cols, "transaction_type", self.transaction_type, schema);
         with_series!(cols, "max_priority_fee_per_gas", self.max_priority_fee_per_gas, schema);
         with_series!(cols, "max_fee_per_gas", self.max_fee_per_gas, schema);
+        with_series!(cols, "max_fee_per_blob_gas", self.max_fee_per_blob_gas, schema);
+        with_series_binary_list!(cols, "blob_versioned_hashes", self.blob_versioned_hashes, schema);
+        with_series_binary!(cols, "function_selector", self.function_selector, schema);
+        with_series!(cols, "function_name", self.function_name, schema);
+        with_series!(cols, "function_inputs", self.function_inputs, schema);
 
         if schema.has_column("chain_id") {
             cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
@@ -371,14 +491,43 @@ fn process_block<TX>(block: &Block<TX>, schema: &Table, columns: &mut BlockColum
     if schema.has_column("base_fee_per_gas") {
         columns.base_fee_per_gas.push(block.base_fee_per_gas.map(|value| value.as_u64()));
     }
+    if schema.has_column("withdrawals_root") {
+        columns.withdrawals_root.push(block.withdrawals_root.map(|x| x.as_bytes().to_vec()));
+    }
+    if schema.has_column("uncles_hash") {
+        columns.uncles_hash.push(block.uncles_hash.as_bytes().to_vec());
+    }
+    if schema.has_column("uncle_count") {
+        columns.uncle_count.push(block.uncles.len() as u32);
+    }
+    // post-dencun header fields are not parsed by ethers, read them from the extra fields
+    if schema.has_column("blob_gas_used") {
+        columns.blob_gas_used.push(other_u64(&block.other, "blobGasUsed"));
+    }
+    if schema.has_column("excess_blob_gas") {
+        columns.excess_blob_gas.push(other_u64(&block.other, "excessBlobGas"));
+    }
+    if schema.has_column("parent_beacon_block_root") {
+        columns.parent_beacon_block_root.push(
+            block
+                .other
+                .get_deserialized::<H256>("parentBeaconBlockRoot")
+                .and_then(|x| x.ok())
+                .map(|x| x.as_bytes().to_vec()),
+        );
+    }
 }
 
-fn process_transaction(
+pub(crate) fn process_transaction(
     tx: &Transaction,
     schema: &Table,
     columns: &mut TransactionColumns,
     gas_used: Option<u32>,
-) {
+    filter: &TransactionFilter,
+) -> bool {
+    if !filter.matches(tx) {
+        return false
+    }
     if schema.has_column("block_number") {
         match tx.block_number {
             Some(block_number) => columns.block_number.push(Some(block_number.as_u64())),
@@ -434,4 +583,41 @@ fn process_transaction(
     if schema.has_column("max_fee_per_gas") {
         columns.max_fee_per_gas.push(tx.max_fee_per_gas.map(|value| value.as_u64()));
     }
+    if schema.has_column("max_fee_per_blob_gas") {
+        columns.max_fee_per_blob_gas.push(other_u64(&tx.other, "maxFeePerBlobGas"));
+    }
+    if schema.has_column("blob_versioned_hashes") {
+        columns
+            .blob_versioned_hashes
+            .push(blob_versioned_hashes(tx).iter().map(|hash| hash.as_bytes().to_vec()).collect());
+    }
+    if schema.has_column("function_selector") {
+        columns.function_selector.push(conversions::call_selector(&tx.input).map(|x| x.to_vec()));
+    }
+
+    // contract creations carry init code rather than call data
+    let decoded = match tx.to {
+        Some(_) => conversions::decode_function_input(&filter.functions, &tx.input),
+        None => None,
+    };
+    if schema.has_column("function_name") {
+        columns.function_name.push(decoded.as_ref().map(|(function, _)| function.name.clone()));
+    }
+    if schema.has_column("function_inputs") {
+        columns.function_inputs.push(decoded.map(|(_, inputs)| inputs));
+    }
+    true
+}
+
+/// read a quantity from fields that ethers does not parse
+pub(crate) fn other_u64(other: &OtherFields, key: &str) -> Option<u64> {
+    other.get_deserialized::<U256>(key).and_then(|x| x.ok()).map(|x| x.as_u64())
+}
+
+/// versioned hashes of the blobs carried by a transaction
+pub(crate) fn blob_versioned_hashes(tx: &Transaction) -> Vec<H256> {
+    tx.other
+        .get_deserialized::<Vec<H256>>("blobVersionedHashes")
+        .and_then(|x| x.ok())
+        .unwrap_or_default()
 }
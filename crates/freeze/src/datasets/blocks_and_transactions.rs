@@ -25,7 +25,7 @@ impl MultiDataset for BlocksAndTransactions {
         chunk: &BlockChunk,
         source: &Source,
         schemas: HashMap<Datatype, Table>,
-        _filter: HashMap<Datatype, RowFilter>,
+        filter: HashMap<Datatype, RowFilter>,
     ) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
         let include_gas_used = match &schemas.get(&Datatype::Transactions) {
             Some(table) => table.has_column("gas_used"),
@@ -36,6 +36,7 @@ impl MultiDataset for BlocksAndTransactions {
             rx,
             &schemas.get(&Datatype::Blocks),
             &schemas.get(&Datatype::Transactions),
+            filter.get(&Datatype::Transactions),
             source.chain_id,
         )
         .await;
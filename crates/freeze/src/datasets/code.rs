@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use ethers::prelude::*;
+use polars::prelude::*;
+use tokio::sync::mpsc;
+
+use super::balances;
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        conversions::ToVecHex, AddressChunk, BlockChunk, Code, CollectError, ColumnType, Dataset,
+        Datatype, RowFilter, Source, Table,
+    },
+    with_series, with_series_binary,
+};
+
+#[async_trait::async_trait]
+impl Dataset for Code {
+    fn datatype(&self) -> Datatype {
+        Datatype::Code
+    }
+
+    fn name(&self) -> &'static str {
+        "code"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("address", ColumnType::Binary),
+            ("code", ColumnType::Binary),
+            ("code_hash", ColumnType::Binary),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec!["block_number", "address", "code_hash"]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["address".to_string(), "block_number".to_string()]
+    }
+
+    async fn collect_address_chunk(
+        &self,
+        chunk: &AddressChunk,
+        blocks: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        _filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let pairs = balances::address_block_pairs(chunk, blocks)?;
+        let rx = balances::fetch_address_block_values(
+            pairs,
+            source,
+            |provider, address, block| async move { provider.get_code(address, Some(block)).await },
+        )
+        .await;
+        code_to_df(rx, schema, source.chain_id).await
+    }
+}
+
+async fn code_to_df(
+    mut rx: mpsc::Receiver<Result<(H160, u64, Bytes), CollectError>>,
+    schema: &Table,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut address: Vec<Vec<u8>> = Vec::new();
+    let mut code: Vec<Vec<u8>> = Vec::new();
+    let mut code_hash: Vec<Vec<u8>> = Vec::new();
+
+    let mut n_rows = 0;
+    while let Some(message) = rx.recv().await {
+        match message {
+            Ok((addr, number, contents)) => {
+                n_rows += 1;
+                block_number.push(number as u32);
+                address.push(addr.as_bytes().to_vec());
+                code_hash.push(ethers::utils::keccak256(&contents).to_vec());
+                if schema.has_column("code") {
+                    code.push(contents.to_vec());
+                }
+            }
+            Err(e) => return Err(CollectError::RPCError(e.to_string())),
+        }
+    }
+
+    let mut cols = Vec::new();
+    with_series!(cols, "block_number", block_number, schema);
+    with_series_binary!(cols, "address", address, schema);
+    with_series_binary!(cols, "code", code, schema);
+    with_series_binary!(cols, "code_hash", code_hash, schema);
+
+    if schema.has_column("chain_id") {
+        cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+    }
+
+    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use ethers::prelude::*;
+use polars::prelude::*;
+use tokio::sync::mpsc;
+
+use super::traces;
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        conversions::ToVecHex, BlockChunk, CollectError, ColumnType, Contracts, Dataset, Datatype,
+        RowFilter, Source, Table,
+    },
+    with_series, with_series_binary,
+};
+
+#[async_trait::async_trait]
+impl Dataset for Contracts {
+    fn datatype(&self) -> Datatype {
+        Datatype::Contracts
+    }
+
+    fn name(&self) -> &'static str {
+        "contracts"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("transaction_position", ColumnType::UInt32),
+            ("transaction_hash", ColumnType::Binary),
+            ("trace_address", ColumnType::String),
+            ("contract_address", ColumnType::Binary),
+            ("deployer", ColumnType::Binary),
+            ("factory", ColumnType::Binary),
+            ("init_code", ColumnType::Binary),
+            ("init_code_hash", ColumnType::Binary),
+            ("code", ColumnType::Binary),
+            ("code_hash", ColumnType::Binary),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec![
+            "block_number",
+            "transaction_position",
+            "transaction_hash",
+            "contract_address",
+            "deployer",
+            "factory",
+            "init_code_hash",
+            "code_hash",
+        ]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["block_number".to_string(), "transaction_position".to_string()]
+    }
+
+    async fn collect_block_chunk(
+        &self,
+        chunk: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        _filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let rx = traces::fetch_traces(chunk, source).await;
+        contracts_to_df(rx, schema, source.chain_id).await
+    }
+}
+
+async fn contracts_to_df(
+    mut rx: mpsc::Receiver<Result<Vec<Trace>, CollectError>>,
+    schema: &Table,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut transaction_position: Vec<u32> = Vec::new();
+    let mut transaction_hash: Vec<Vec<u8>> = Vec::new();
+    let mut trace_address: Vec<String> = Vec::new();
+    let mut contract_address: Vec<Vec<u8>> = Vec::new();
+    let mut deployer: Vec<Vec<u8>> = Vec::new();
+    let mut factory: Vec<Vec<u8>> = Vec::new();
+    let mut init_code: Vec<Vec<u8>> = Vec::new();
+    let mut init_code_hash: Vec<Vec<u8>> = Vec::new();
+    let mut code: Vec<Vec<u8>> = Vec::new();
+    let mut code_hash: Vec<Vec<u8>> = Vec::new();
+
+    let mut n_rows = 0;
+    while let Some(message) = rx.recv().await {
+        match message {
+            Ok(block_traces) => {
+                // sender of the current transaction and its failed trace addresses
+                let mut current_tx: Option<H256> = None;
+                let mut tx_from: Option<H160> = None;
+                let mut failed: Vec<Vec<usize>> = Vec::new();
+                for trace in block_traces.iter() {
+                    let (tx_hash, tx_pos) =
+                        match (trace.transaction_hash, trace.transaction_position) {
+                            (Some(tx_hash), Some(tx_pos)) => (tx_hash, tx_pos),
+                            _ => continue,
+                        };
+                    if current_tx != Some(tx_hash) {
+                        current_tx = Some(tx_hash);
+                        tx_from = None;
+                        failed.clear();
+                    }
+                    if trace.trace_address.is_empty() {
+                        tx_from = match &trace.action {
+                            Action::Call(action) => Some(action.from),
+                            Action::Create(action) => Some(action.from),
+                            _ => None,
+                        };
+                    }
+
+                    // contracts created by reverted frames are never deployed
+                    if trace.error.is_some() {
+                        failed.push(trace.trace_address.clone());
+                        continue
+                    }
+                    if traces::has_failed_parent(&trace.trace_address, &failed) {
+                        continue
+                    }
+
+                    if let (Action::Create(action), Some(Res::Create(result)), Some(from)) =
+                        (&trace.action, &trace.result, tx_from)
+                    {
+                        n_rows += 1;
+                        block_number.push(trace.block_number as u32);
+                        transaction_position.push(tx_pos as u32);
+                        transaction_hash.push(tx_hash.as_bytes().to_vec());
+                        trace_address.push(
+                            trace
+                                .trace_address
+                                .iter()
+                                .map(|n| n.to_string())
+                                .collect::<Vec<String>>()
+                                .join("_"),
+                        );
+                        contract_address.push(result.address.as_bytes().to_vec());
+                        deployer.push(from.as_bytes().to_vec());
+                        factory.push(action.from.as_bytes().to_vec());
+                        if schema.has_column("init_code") {
+                            init_code.push(action.init.to_vec());
+                        }
+                        init_code_hash.push(ethers::utils::keccak256(&action.init).to_vec());
+                        if schema.has_column("code") {
+                            code.push(result.code.to_vec());
+                        }
+                        code_hash.push(ethers::utils::keccak256(&result.code).to_vec());
+                    }
+                }
+            }
+            Err(e) => return Err(CollectError::RPCError(e.to_string())),
+        }
+    }
+
+    let mut cols = Vec::new();
+    with_series!(cols, "block_number", block_number, schema);
+    with_series!(cols, "transaction_position", transaction_position, schema);
+    with_series_binary!(cols, "transaction_hash", transaction_hash, schema);
+    with_series!(cols, "trace_address", trace_address, schema);
+    with_series_binary!(cols, "contract_address", contract_address, schema);
+    with_series_binary!(cols, "deployer", deployer, schema);
+    with_series_binary!(cols, "factory", factory, schema);
+    with_series_binary!(cols, "init_code", init_code, schema);
+    with_series_binary!(cols, "init_code_hash", init_code_hash, schema);
+    with_series_binary!(cols, "code", code, schema);
+    with_series_binary!(cols, "code_hash", code_hash, schema);
+
+    if schema.has_column("chain_id") {
+        cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+    }
+
+    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+}
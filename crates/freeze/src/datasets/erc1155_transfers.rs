@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use ethers::{
+    abi::{ParamType, Token},
+    prelude::*,
+};
+use polars::prelude::*;
+use tokio::sync::mpsc;
+
+use super::logs;
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        conversions::{u256_to_bytes, ToVecHex},
+        BlockChunk, CollectError, ColumnType, Dataset, Datatype, Erc1155Transfers, RowFilter,
+        Source, Table,
+    },
+    with_series, with_series_binary,
+};
+
+fn transfer_single_topic() -> H256 {
+    H256(ethers::utils::keccak256("TransferSingle(address,address,address,uint256,uint256)"))
+}
+
+fn transfer_batch_topic() -> H256 {
+    H256(ethers::utils::keccak256("TransferBatch(address,address,address,uint256[],uint256[])"))
+}
+
+#[async_trait::async_trait]
+impl Dataset for Erc1155Transfers {
+    fn datatype(&self) -> Datatype {
+        Datatype::Erc1155Transfers
+    }
+
+    fn name(&self) -> &'static str {
+        "erc1155_transfers"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("transaction_index", ColumnType::UInt32),
+            ("log_index", ColumnType::UInt32),
+            ("batch_index", ColumnType::UInt32),
+            ("transaction_hash", ColumnType::Binary),
+            ("token", ColumnType::Binary),
+            ("operator", ColumnType::Binary),
+            ("from_address", ColumnType::Binary),
+            ("to_address", ColumnType::Binary),
+            ("token_id", ColumnType::Binary),
+            ("token_id_string", ColumnType::String),
+            ("amount", ColumnType::Binary),
+            ("amount_string", ColumnType::String),
+            ("amount_float", ColumnType::Float64),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec![
+            "block_number",
+            "transaction_index",
+            "log_index",
+            "batch_index",
+            "transaction_hash",
+            "token",
+            "operator",
+            "from_address",
+            "to_address",
+            "token_id",
+            "amount",
+        ]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["block_number".to_string(), "log_index".to_string(), "batch_index".to_string()]
+    }
+
+    async fn collect_block_chunk(
+        &self,
+        chunk: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let topic0 =
+            ValueOrArray::Array(vec![Some(transfer_single_topic()), Some(transfer_batch_topic())]);
+        let filter = logs::event_filter(topic0, filter);
+        let rx = logs::fetch_logs(chunk, source, Some(&filter)).await;
+        erc1155_transfers_to_df(rx, schema, source.chain_id).await
+    }
+}
+
+/// decode (ids, values) arrays of a TransferBatch event into (id, value) pairs
+fn decode_batch(data: &[u8]) -> Option<Vec<(U256, U256)>> {
+    let array = ParamType::Array(Box::new(ParamType::Uint(256)));
+    let tokens = ethers::abi::decode(&[array.clone(), array], data).ok()?;
+    match tokens.as_slice() {
+        [Token::Array(ids), Token::Array(values)] if ids.len() == values.len() => ids
+            .iter()
+            .zip(values)
+            .map(|(id, value)| match (id, value) {
+                (Token::Uint(id), Token::Uint(value)) => Some((*id, *value)),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+async fn erc1155_transfers_to_df(
+    mut rx: mpsc::Receiver<Result<Vec<Log>, CollectError>>,
+    schema: &Table,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut transaction_index: Vec<u32> = Vec::new();
+    let mut log_index: Vec<u32> = Vec::new();
+    let mut batch_index: Vec<u32> = Vec::new();
+    let mut transaction_hash: Vec<Vec<u8>> = Vec::new();
+    let mut token: Vec<Vec<u8>> = Vec::new();
+    let mut operator: Vec<Vec<u8>> = Vec::new();
+    let mut from_address: Vec<Vec<u8>> = Vec::new();
+    let mut to_address: Vec<Vec<u8>> = Vec::new();
+    let mut token_id: Vec<Vec<u8>> = Vec::new();
+    let mut token_id_string: Vec<String> = Vec::new();
+    let mut amount: Vec<Vec<u8>> = Vec::new();
+    let mut amount_string: Vec<String> = Vec::new();
+    let mut amount_float: Vec<Option<f64>> = Vec::new();
+
+    let single_topic = transfer_single_topic();
+    let mut n_rows = 0;
+    while let Some(message) = rx.recv().await {
+        match message {
+            Ok(logs) => {
+                for log in logs.iter() {
+                    if let Some(true) = log.removed {
+                        continue
+                    }
+                    if log.topics.len() != 4 {
+                        continue
+                    }
+                    let transfers = if log.topics[0] == single_topic {
+                        if log.data.len() != 64 {
+                            continue
+                        }
+                        vec![(
+                            U256::from_big_endian(&log.data[..32]),
+                            U256::from_big_endian(&log.data[32..]),
+                        )]
+                    } else {
+                        match decode_batch(&log.data) {
+                            Some(transfers) => transfers,
+                            None => continue,
+                        }
+                    };
+                    if let (Some(bn), Some(tx), Some(ti), Some(li)) = (
+                        log.block_number,
+                        log.transaction_hash,
+                        log.transaction_index,
+                        log.log_index,
+                    ) {
+                        // batches are exploded into one row per (id, amount)
+                        for (index, (id, value)) in transfers.iter().enumerate() {
+                            n_rows += 1;
+                            block_number.push(bn.as_u32());
+                            transaction_index.push(ti.as_u32());
+                            log_index.push(li.as_u32());
+                            batch_index.push(index as u32);
+                            transaction_hash.push(tx.as_bytes().to_vec());
+                            token.push(log.address.as_bytes().to_vec());
+                            operator.push(log.topics[1].as_bytes()[12..].to_vec());
+                            from_address.push(log.topics[2].as_bytes()[12..].to_vec());
+                            to_address.push(log.topics[3].as_bytes()[12..].to_vec());
+                            token_id.push(u256_to_bytes(id));
+                            token_id_string.push(id.to_string());
+                            amount.push(u256_to_bytes(value));
+                            amount_string.push(value.to_string());
+                            amount_float.push(value.to_string().parse::<f64>().ok());
+                        }
+                    }
+                }
+            }
+            _ => return Err(CollectError::TooManyRequestsError),
+        }
+    }
+
+    let mut cols = Vec::new();
+    with_series!(cols, "block_number", block_number, schema);
+    with_series!(cols, "transaction_index", transaction_index, schema);
+    with_series!(cols, "log_index", log_index, schema);
+    with_series!(cols, "batch_index", batch_index, schema);
+    with_series_binary!(cols, "transaction_hash", transaction_hash, schema);
+    with_series_binary!(cols, "token", token, schema);
+    with_series_binary!(cols, "operator", operator, schema);
+    with_series_binary!(cols, "from_address", from_address, schema);
+    with_series_binary!(cols, "to_address", to_address, schema);
+    with_series_binary!(cols, "token_id", token_id, schema);
+    with_series!(cols, "token_id_string", token_id_string, schema);
+    with_series_binary!(cols, "amount", amount, schema);
+    with_series!(cols, "amount_string", amount_string, schema);
+    with_series!(cols, "amount_float", amount_float, schema);
+
+    if schema.has_column("chain_id") {
+        cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+    }
+
+    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use ethers::prelude::*;
+use polars::prelude::*;
+use tokio::sync::mpsc;
+
+use super::logs;
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        conversions::ToVecHex, BlockChunk, CollectError, ColumnType, Dataset, Datatype,
+        Erc20Transfers, RowFilter, Source, Table,
+    },
+    with_series, with_series_binary,
+};
+
+/// topic0 of Transfer(address,address,uint256), shared by ERC-20 and ERC-721
+pub(crate) fn transfer_topic() -> H256 {
+    H256(ethers::utils::keccak256("Transfer(address,address,uint256)"))
+}
+
+#[async_trait::async_trait]
+impl Dataset for Erc20Transfers {
+    fn datatype(&self) -> Datatype {
+        Datatype::Erc20Transfers
+    }
+
+    fn name(&self) -> &'static str {
+        "erc20_transfers"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("transaction_index", ColumnType::UInt32),
+            ("log_index", ColumnType::UInt32),
+            ("transaction_hash", ColumnType::Binary),
+            ("token", ColumnType::Binary),
+            ("from_address", ColumnType::Binary),
+            ("to_address", ColumnType::Binary),
+            ("amount", ColumnType::Binary),
+            ("amount_string", ColumnType::String),
+            ("amount_float", ColumnType::Float64),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec![
+            "block_number",
+            "transaction_index",
+            "log_index",
+            "transaction_hash",
+            "token",
+            "from_address",
+            "to_address",
+            "amount",
+        ]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["block_number".to_string(), "log_index".to_string()]
+    }
+
+    async fn collect_block_chunk(
+        &self,
+        chunk: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let filter = logs::event_filter(ValueOrArray::Value(Some(transfer_topic())), filter);
+        let rx = logs::fetch_logs(chunk, source, Some(&filter)).await;
+        erc20_transfers_to_df(rx, schema, source.chain_id).await
+    }
+}
+
+async fn erc20_transfers_to_df(
+    mut rx: mpsc::Receiver<Result<Vec<Log>, CollectError>>,
+    schema: &Table,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut transaction_index: Vec<u32> = Vec::new();
+    let mut log_index: Vec<u32> = Vec::new();
+    let mut transaction_hash: Vec<Vec<u8>> = Vec::new();
+    let mut token: Vec<Vec<u8>> = Vec::new();
+    let mut from_address: Vec<Vec<u8>> = Vec::new();
+    let mut to_address: Vec<Vec<u8>> = Vec::new();
+    let mut amount: Vec<Vec<u8>> = Vec::new();
+    let mut amount_string: Vec<String> = Vec::new();
+    let mut amount_float: Vec<Option<f64>> = Vec::new();
+
+    let mut n_rows = 0;
+    while let Some(message) = rx.recv().await {
+        match message {
+            Ok(logs) => {
+                for log in logs.iter() {
+                    if let Some(true) = log.removed {
+                        continue
+                    }
+                    // ERC-721 transfers share topic0 but index the token id as a fourth topic
+                    if (log.topics.len() != 3) | (log.data.len() != 32) {
+                        continue
+                    }
+                    if let (Some(bn), Some(tx), Some(ti), Some(li)) = (
+                        log.block_number,
+                        log.transaction_hash,
+                        log.transaction_index,
+                        log.log_index,
+                    ) {
+                        n_rows += 1;
+                        let value = U256::from_big_endian(&log.data);
+                        block_number.push(bn.as_u32());
+                        transaction_index.push(ti.as_u32());
+                        log_index.push(li.as_u32());
+                        transaction_hash.push(tx.as_bytes().to_vec());
+                        token.push(log.address.as_bytes().to_vec());
+                        from_address.push(log.topics[1].as_bytes()[12..].to_vec());
+                        to_address.push(log.topics[2].as_bytes()[12..].to_vec());
+                        amount.push(log.data.to_vec());
+                        amount_string.push(value.to_string());
+                        amount_float.push(value.to_string().parse::<f64>().ok());
+                    }
+                }
+            }
+            _ => return Err(CollectError::TooManyRequestsError),
+        }
+    }
+
+    let mut cols = Vec::new();
+    with_series!(cols, "block_number", block_number, schema);
+    with_series!(cols, "transaction_index", transaction_index, schema);
+    with_series!(cols, "log_index", log_index, schema);
+    with_series_binary!(cols, "transaction_hash", transaction_hash, schema);
+    with_series_binary!(cols, "token", token, schema);
+    with_series_binary!(cols, "from_address", from_address, schema);
+    with_series_binary!(cols, "to_address", to_address, schema);
+    with_series_binary!(cols, "amount", amount, schema);
+    with_series!(cols, "amount_string", amount_string, schema);
+    with_series!(cols, "amount_float", amount_float, schema);
+
+    if schema.has_column("chain_id") {
+        cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+    }
+
+    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+}
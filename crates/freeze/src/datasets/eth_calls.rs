@@ -0,0 +1,167 @@
+use std::{collections::HashMap, sync::Arc};
+
+use ethers::prelude::*;
+use polars::prelude::*;
+use tokio::{sync::mpsc, task};
+
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        conversions::{format_tokens, ToVecHex},
+        BlockChunk, CollectError, ColumnType, Dataset, Datatype, EthCalls, RowFilter, Source,
+        Table,
+    },
+    with_series, with_series_binary,
+};
+
+#[async_trait::async_trait]
+impl Dataset for EthCalls {
+    fn datatype(&self) -> Datatype {
+        Datatype::EthCalls
+    }
+
+    fn name(&self) -> &'static str {
+        "eth_calls"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("contract_address", ColumnType::Binary),
+            ("call_data", ColumnType::Binary),
+            ("output_data", ColumnType::Binary),
+            ("output_decoded", ColumnType::String),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec!["block_number", "contract_address", "call_data", "output_data", "output_decoded"]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["block_number".to_string(), "contract_address".to_string()]
+    }
+
+    async fn collect_block_chunk(
+        &self,
+        chunk: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let (contracts, call_data, function) = match filter {
+            Some(RowFilter {
+                address: Some(address),
+                call_data: Some(call_data),
+                function: Some(function),
+                ..
+            }) => {
+                let contracts = match address {
+                    ValueOrArray::Value(address) => vec![*address],
+                    ValueOrArray::Array(addresses) => addresses.clone(),
+                };
+                (contracts, call_data.clone(), function)
+            }
+            _ => {
+                return Err(CollectError::CollectError(
+                    "eth_calls requires a contract and a function to call".to_string(),
+                ))
+            }
+        };
+        let rx = fetch_eth_calls(chunk, contracts, call_data.clone(), source).await;
+        eth_calls_to_df(rx, call_data, function, schema, source.chain_id).await
+    }
+}
+
+async fn fetch_eth_calls(
+    block_chunk: &BlockChunk,
+    contracts: Vec<H160>,
+    call_data: Vec<u8>,
+    source: &Source,
+) -> mpsc::Receiver<Result<(u64, H160, Option<Bytes>), CollectError>> {
+    let (tx, rx) = mpsc::channel((block_chunk.numbers().len() * contracts.len()).max(1));
+    let source = Arc::new(source.clone());
+
+    for number in block_chunk.numbers() {
+        for contract in contracts.iter() {
+            let contract = *contract;
+            let call: TypedTransaction =
+                TransactionRequest::new().to(contract).data(call_data.clone()).into();
+            let tx = tx.clone();
+            let source = Arc::clone(&source);
+            task::spawn(async move {
+                let permit = match source.semaphore.clone() {
+                    Some(semaphore) => Some(semaphore.acquire_owned().await),
+                    _ => None,
+                };
+                if let Some(limiter) = source.rate_limiter.as_ref() {
+                    Arc::clone(limiter).until_ready().await;
+                }
+                let block = BlockId::Number(BlockNumber::Number(number.into()));
+                let result = match source.provider.call(&call, Some(block)).await {
+                    Ok(output) => Ok((number, contract, Some(output))),
+                    // reverted calls are kept as rows without output
+                    Err(e) if e.as_error_response().map_or(false, |r| r.is_revert()) => {
+                        Ok((number, contract, None))
+                    }
+                    Err(e) => Err(CollectError::ProviderError(e)),
+                };
+                drop(permit);
+                match tx.send(result).await {
+                    Ok(_) => {}
+                    Err(tokio::sync::mpsc::error::SendError(_e)) => {
+                        eprintln!("send error, try using a rate limit with --requests-per-second or limiting max concurrency with --max-concurrent-requests");
+                        std::process::exit(1)
+                    }
+                }
+            });
+        }
+    }
+    rx
+}
+
+async fn eth_calls_to_df(
+    mut rx: mpsc::Receiver<Result<(u64, H160, Option<Bytes>), CollectError>>,
+    call_data: Vec<u8>,
+    function: &ethers::abi::Function,
+    schema: &Table,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut contract_address: Vec<Vec<u8>> = Vec::new();
+    let mut output_data: Vec<Option<Vec<u8>>> = Vec::new();
+    let mut output_decoded: Vec<Option<String>> = Vec::new();
+
+    let mut n_rows = 0;
+    while let Some(message) = rx.recv().await {
+        match message {
+            Ok((number, contract, output)) => {
+                n_rows += 1;
+                block_number.push(number as u32);
+                contract_address.push(contract.as_bytes().to_vec());
+                output_decoded.push(
+                    output
+                        .as_ref()
+                        .and_then(|output| function.decode_output(output).ok())
+                        .map(|tokens| format_tokens(&tokens)),
+                );
+                output_data.push(output.map(|output| output.to_vec()));
+            }
+            Err(e) => return Err(CollectError::RPCError(e.to_string())),
+        }
+    }
+
+    let mut cols = Vec::new();
+    with_series!(cols, "block_number", block_number, schema);
+    with_series_binary!(cols, "contract_address", contract_address, schema);
+    with_series_binary!(cols, "call_data", vec![call_data; n_rows], schema);
+    with_series_binary!(cols, "output_data", output_data, schema);
+    with_series!(cols, "output_decoded", output_decoded, schema);
+
+    if schema.has_column("chain_id") {
+        cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+    }
+
+    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+}
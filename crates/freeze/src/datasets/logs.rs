@@ -1,14 +1,18 @@
 use std::{collections::HashMap, sync::Arc};
 
-use ethers::prelude::*;
+use ethers::{
+    abi::{Event, RawLog, Token},
+    prelude::*,
+};
 use polars::prelude::*;
 use tokio::{sync::mpsc, task};
 
 use crate::{
     dataframes::SortableDataFrame,
     types::{
-        conversions::ToVecHex, BlockChunk, CollectError, ColumnType, Dataset, Datatype, Logs,
-        RowFilter, Source, Table,
+        conversions::{tokens_to_series, ToVecHex},
+        schemas::param_column_name,
+        BlockChunk, CollectError, ColumnType, Dataset, Datatype, Logs, RowFilter, Source, Table,
     },
     with_series, with_series_binary,
 };
@@ -66,11 +70,27 @@ impl Dataset for Logs {
         filter: Option<&RowFilter>,
     ) -> Result<DataFrame, CollectError> {
         let rx = fetch_logs(chunk, source, filter).await;
-        logs_to_df(rx, schema, source.chain_id).await
+        let events = filter.and_then(|filter| filter.events.as_deref()).unwrap_or_default();
+        logs_to_df(rx, schema, source.chain_id, events).await
+    }
+}
+
+/// build a RowFilter that pins topic0 to an event signature, keeping the other filters
+pub(crate) fn event_filter(
+    topic0: ValueOrArray<Option<H256>>,
+    filter: Option<&RowFilter>,
+) -> RowFilter {
+    match filter {
+        Some(filter) => {
+            let mut filter = filter.clone();
+            filter.topics[0] = Some(topic0);
+            filter
+        }
+        None => RowFilter { topics: [Some(topic0), None, None, None], ..Default::default() },
     }
 }
 
-async fn fetch_logs(
+pub(crate) async fn fetch_logs(
     block_chunk: &BlockChunk,
     source: &Source,
     filter: Option<&RowFilter>,
@@ -119,6 +139,7 @@ async fn logs_to_df(
     mut logs: mpsc::Receiver<Result<Vec<Log>, CollectError>>,
     schema: &Table,
     chain_id: u64,
+    events: &[Event],
 ) -> Result<DataFrame, CollectError> {
     let mut block_number: Vec<u32> = Vec::new();
     let mut transaction_index: Vec<u32> = Vec::new();
@@ -131,6 +152,20 @@ async fn logs_to_df(
     let mut topic3: Vec<Option<Vec<u8>>> = Vec::new();
     let mut data: Vec<Vec<u8>> = Vec::new();
 
+    // decoded event parameters, one column per parameter name, events are keyed by topic0 and
+    // number of indexed parameters so that e.g. erc20 and erc721 Transfer events are told apart
+    let mut decoders: HashMap<(H256, usize), &Event> = HashMap::new();
+    for event in events.iter() {
+        let n_indexed = event.inputs.iter().filter(|input| input.indexed).count();
+        decoders.entry((event.signature(), n_indexed)).or_insert(event);
+    }
+    let mut decoded: Vec<(String, Vec<Option<Token>>)> = schema
+        .columns()
+        .into_iter()
+        .filter(|column| column.starts_with("event__"))
+        .map(|column| (column.to_string(), Vec::new()))
+        .collect();
+
     let mut n_rows = 0;
     // while let Some(Ok(logs)) = logs.recv().await {
     while let Some(message) = logs.recv().await {
@@ -182,6 +217,12 @@ async fn logs_to_df(
                             _ => return Err(CollectError::InvalidNumberOfTopics),
                         }
                         data.push(log.data.clone().to_vec());
+                        if !decoded.is_empty() {
+                            let mut params = decode_log(log, &decoders);
+                            for (column, values) in decoded.iter_mut() {
+                                values.push(params.remove(column));
+                            }
+                        }
                         block_number.push(bn.as_u32());
                         transaction_hash.push(tx.as_bytes().to_vec());
                         transaction_index.push(ti.as_u32());
@@ -204,6 +245,11 @@ async fn logs_to_df(
     with_series_binary!(cols, "topic2", topic2, schema);
     with_series_binary!(cols, "topic3", topic3, schema);
     with_series_binary!(cols, "data", data, schema);
+    for (column, values) in decoded.into_iter() {
+        if let Some(column_type) = schema.column_type(&column) {
+            cols.push(tokens_to_series(&column, values, column_type));
+        }
+    }
 
     if schema.has_column("chain_id") {
         cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
@@ -211,3 +257,25 @@ async fn logs_to_df(
 
     DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
 }
+
+/// decode the parameters of a log matching one of the events, keyed by column name
+fn decode_log(log: &Log, decoders: &HashMap<(H256, usize), &Event>) -> HashMap<String, Token> {
+    let n_indexed = log.topics.len().saturating_sub(1);
+    let event = match log.topics.first().and_then(|topic0| decoders.get(&(*topic0, n_indexed))) {
+        Some(event) => event,
+        None => return HashMap::new(),
+    };
+    let raw_log = RawLog { topics: log.topics.clone(), data: log.data.to_vec() };
+    match event.parse_log(raw_log) {
+        Ok(parsed) => event
+            .inputs
+            .iter()
+            .enumerate()
+            .zip(parsed.params)
+            .map(|((index, input), param)| {
+                (param_column_name("event", index, &input.name), param.value)
+            })
+            .collect(),
+        Err(_) => HashMap::new(),
+    }
+}
@@ -1,11 +1,26 @@
+mod address_appearances;
 mod balance_diffs;
+mod balances;
+mod blobs;
 mod blocks;
 mod blocks_and_transactions;
+mod code;
 mod code_diffs;
+mod contracts;
+mod erc1155_transfers;
+mod erc20_transfers;
+mod erc721_transfers;
+mod eth_calls;
 mod logs;
+mod native_transfers;
 mod nonce_diffs;
+mod nonces;
+mod receipts;
+mod slots;
 mod state_diffs;
 mod storage_diffs;
 mod traces;
 mod transactions;
+mod uncles;
 mod vm_traces;
+mod withdrawals;
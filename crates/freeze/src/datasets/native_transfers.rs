@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use ethers::prelude::*;
+use polars::prelude::*;
+use tokio::sync::mpsc;
+
+use super::traces;
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        conversions::{u256_to_bytes, ToVecHex},
+        BlockChunk, CollectError, ColumnType, Dataset, Datatype, NativeTransfers, RowFilter,
+        Source, Table,
+    },
+    with_series, with_series_binary,
+};
+
+#[async_trait::async_trait]
+impl Dataset for NativeTransfers {
+    fn datatype(&self) -> Datatype {
+        Datatype::NativeTransfers
+    }
+
+    fn name(&self) -> &'static str {
+        "native_transfers"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("transaction_position", ColumnType::UInt32),
+            ("transaction_hash", ColumnType::Binary),
+            ("trace_address", ColumnType::String),
+            ("transfer_type", ColumnType::String),
+            ("from_address", ColumnType::Binary),
+            ("to_address", ColumnType::Binary),
+            ("value", ColumnType::Binary),
+            ("value_string", ColumnType::String),
+            ("value_float", ColumnType::Float64),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec![
+            "block_number",
+            "transaction_position",
+            "transaction_hash",
+            "trace_address",
+            "from_address",
+            "to_address",
+            "value",
+        ]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["block_number".to_string(), "transaction_position".to_string()]
+    }
+
+    async fn collect_block_chunk(
+        &self,
+        chunk: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        _filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let rx = traces::fetch_traces(chunk, source).await;
+        native_transfers_to_df(rx, schema, source.chain_id).await
+    }
+}
+
+async fn native_transfers_to_df(
+    mut rx: mpsc::Receiver<Result<Vec<Trace>, CollectError>>,
+    schema: &Table,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut transaction_position: Vec<u32> = Vec::new();
+    let mut transaction_hash: Vec<Vec<u8>> = Vec::new();
+    let mut trace_address: Vec<String> = Vec::new();
+    let mut transfer_type: Vec<String> = Vec::new();
+    let mut from_address: Vec<Vec<u8>> = Vec::new();
+    let mut to_address: Vec<Vec<u8>> = Vec::new();
+    let mut value: Vec<Vec<u8>> = Vec::new();
+    let mut value_string: Vec<String> = Vec::new();
+    let mut value_float: Vec<Option<f64>> = Vec::new();
+
+    let mut n_rows = 0;
+    while let Some(message) = rx.recv().await {
+        match message {
+            Ok(block_traces) => {
+                // trace addresses of failed traces in the current transaction
+                let mut current_tx: Option<H256> = None;
+                let mut failed: Vec<Vec<usize>> = Vec::new();
+                for trace in block_traces.iter() {
+                    let (tx_hash, tx_pos) =
+                        match (trace.transaction_hash, trace.transaction_position) {
+                            (Some(tx_hash), Some(tx_pos)) => (tx_hash, tx_pos),
+                            _ => continue,
+                        };
+                    if current_tx != Some(tx_hash) {
+                        current_tx = Some(tx_hash);
+                        failed.clear();
+                    }
+
+                    // value moved by failed traces and by their subtraces is reverted
+                    if trace.error.is_some() {
+                        failed.push(trace.trace_address.clone());
+                        continue
+                    }
+                    if traces::has_failed_parent(&trace.trace_address, &failed) {
+                        continue
+                    }
+
+                    let (from, to, amount, kind) = match (&trace.action, &trace.result) {
+                        (Action::Call(action), _) if action.call_type == CallType::DelegateCall => {
+                            continue
+                        }
+                        (Action::Call(action), _) => (action.from, action.to, action.value, "call"),
+                        (Action::Create(action), Some(Res::Create(result))) => {
+                            (action.from, result.address, action.value, "create")
+                        }
+                        _ => continue,
+                    };
+                    if amount.is_zero() {
+                        continue
+                    }
+
+                    n_rows += 1;
+                    block_number.push(trace.block_number as u32);
+                    transaction_position.push(tx_pos as u32);
+                    transaction_hash.push(tx_hash.as_bytes().to_vec());
+                    trace_address.push(
+                        trace
+                            .trace_address
+                            .iter()
+                            .map(|n| n.to_string())
+                            .collect::<Vec<String>>()
+                            .join("_"),
+                    );
+                    transfer_type.push(kind.to_string());
+                    from_address.push(from.as_bytes().to_vec());
+                    to_address.push(to.as_bytes().to_vec());
+                    value.push(u256_to_bytes(&amount));
+                    value_string.push(amount.to_string());
+                    value_float.push(amount.to_string().parse::<f64>().ok());
+                }
+            }
+            Err(e) => return Err(CollectError::RPCError(e.to_string())),
+        }
+    }
+
+    let mut cols = Vec::new();
+    with_series!(cols, "block_number", block_number, schema);
+    with_series!(cols, "transaction_position", transaction_position, schema);
+    with_series_binary!(cols, "transaction_hash", transaction_hash, schema);
+    with_series!(cols, "trace_address", trace_address, schema);
+    with_series!(cols, "transfer_type", transfer_type, schema);
+    with_series_binary!(cols, "from_address", from_address, schema);
+    with_series_binary!(cols, "to_address", to_address, schema);
+    with_series_binary!(cols, "value", value, schema);
+    with_series!(cols, "value_string", value_string, schema);
+    with_series!(cols, "value_float", value_float, schema);
+
+    if schema.has_column("chain_id") {
+        cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+    }
+
+    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+}
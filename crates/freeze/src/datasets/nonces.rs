@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use ethers::prelude::*;
+use polars::prelude::*;
+use tokio::sync::mpsc;
+
+use super::balances;
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        conversions::ToVecHex, AddressChunk, BlockChunk, CollectError, ColumnType, Dataset,
+        Datatype, Nonces, RowFilter, Source, Table,
+    },
+    with_series, with_series_binary,
+};
+
+#[async_trait::async_trait]
+impl Dataset for Nonces {
+    fn datatype(&self) -> Datatype {
+        Datatype::Nonces
+    }
+
+    fn name(&self) -> &'static str {
+        "nonces"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("address", ColumnType::Binary),
+            ("nonce", ColumnType::UInt64),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec!["block_number", "address", "nonce"]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["address".to_string(), "block_number".to_string()]
+    }
+
+    async fn collect_address_chunk(
+        &self,
+        chunk: &AddressChunk,
+        blocks: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        _filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let pairs = balances::address_block_pairs(chunk, blocks)?;
+        let rx = balances::fetch_address_block_values(
+            pairs,
+            source,
+            |provider, address, block| async move {
+                provider.get_transaction_count(address, Some(block)).await
+            },
+        )
+        .await;
+        nonces_to_df(rx, schema, source.chain_id).await
+    }
+}
+
+async fn nonces_to_df(
+    mut rx: mpsc::Receiver<Result<(H160, u64, U256), CollectError>>,
+    schema: &Table,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut address: Vec<Vec<u8>> = Vec::new();
+    let mut nonce: Vec<u64> = Vec::new();
+
+    let mut n_rows = 0;
+    while let Some(message) = rx.recv().await {
+        match message {
+            Ok((addr, number, value)) => {
+                n_rows += 1;
+                block_number.push(number as u32);
+                address.push(addr.as_bytes().to_vec());
+                nonce.push(value.as_u64());
+            }
+            Err(e) => return Err(CollectError::RPCError(e.to_string())),
+        }
+    }
+
+    let mut cols = Vec::new();
+    with_series!(cols, "block_number", block_number, schema);
+    with_series_binary!(cols, "address", address, schema);
+    with_series!(cols, "nonce", nonce, schema);
+
+    if schema.has_column("chain_id") {
+        cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+    }
+
+    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+}
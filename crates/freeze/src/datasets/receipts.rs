@@ -0,0 +1,287 @@
+use std::{
+    collections::HashMap,
+    sync::{atomic::Ordering, Arc},
+};
+
+use ethers::prelude::*;
+use polars::prelude::*;
+use tokio::{sync::mpsc, task};
+
+use super::transactions;
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        conversions::ToVecHex, BlockChunk, CollectError, ColumnType, Dataset, Datatype, Receipts,
+        RowFilter, Source, Table, TransactionChunk,
+    },
+    with_series, with_series_binary,
+};
+
+#[async_trait::async_trait]
+impl Dataset for Receipts {
+    fn datatype(&self) -> Datatype {
+        Datatype::Receipts
+    }
+
+    fn name(&self) -> &'static str {
+        "receipts"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("block_hash", ColumnType::Binary),
+            ("transaction_index", ColumnType::UInt32),
+            ("transaction_hash", ColumnType::Binary),
+            ("from_address", ColumnType::Binary),
+            ("to_address", ColumnType::Binary),
+            ("contract_address", ColumnType::Binary),
+            ("status", ColumnType::UInt32),
+            ("cumulative_gas_used", ColumnType::UInt64),
+            ("gas_used", ColumnType::UInt64),
+            ("effective_gas_price", ColumnType::UInt64),
+            ("transaction_type", ColumnType::UInt32),
+            ("logs_bloom", ColumnType::Binary),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec![
+            "block_number",
+            "transaction_index",
+            "transaction_hash",
+            "contract_address",
+            "status",
+            "cumulative_gas_used",
+            "gas_used",
+            "effective_gas_price",
+            "logs_bloom",
+        ]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["block_number".to_string(), "transaction_index".to_string()]
+    }
+
+    async fn collect_block_chunk(
+        &self,
+        chunk: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        _filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let rx = fetch_receipts(chunk, source).await;
+        receipts_to_df(rx, schema, source.chain_id).await
+    }
+
+    async fn collect_transaction_chunk(
+        &self,
+        chunk: &TransactionChunk,
+        source: &Source,
+        schema: &Table,
+        _filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let hashes = transactions::transaction_hashes(chunk)?;
+        let rx = fetch_transaction_receipts(&hashes, source).await;
+        receipts_to_df(rx, schema, source.chain_id).await
+    }
+}
+
+async fn fetch_transaction_receipts(
+    hashes: &[H256],
+    source: &Source,
+) -> mpsc::Receiver<Result<Vec<TransactionReceipt>, CollectError>> {
+    transactions::fetch_by_hash(hashes, source, |source, hash| async move {
+        match source.provider.get_transaction_receipt(hash).await {
+            Ok(Some(receipt)) => Ok(vec![receipt]),
+            Ok(None) => Err(CollectError::CollectError("could not find tx receipt".to_string())),
+            Err(e) => Err(CollectError::ProviderError(e)),
+        }
+    })
+    .await
+}
+
+async fn fetch_receipts(
+    block_chunk: &BlockChunk,
+    source: &Source,
+) -> mpsc::Receiver<Result<Vec<TransactionReceipt>, CollectError>> {
+    let (tx, rx) = mpsc::channel(block_chunk.numbers().len());
+    let source = Arc::new(source.clone());
+
+    for number in block_chunk.numbers() {
+        let tx = tx.clone();
+        let source = Arc::clone(&source);
+        task::spawn(async move {
+            let result = get_block_receipts(number, source).await;
+            match tx.send(result).await {
+                Ok(_) => {}
+                Err(tokio::sync::mpsc::error::SendError(_e)) => {
+                    eprintln!("send error, try using a rate limit with --requests-per-second or limiting max concurrency with --max-concurrent-requests");
+                    std::process::exit(1)
+                }
+            }
+        });
+    }
+    rx
+}
+
+/// get all receipts of a block, using eth_getBlockReceipts if the node supports it and falling
+/// back to one eth_getTransactionReceipt per transaction otherwise
+pub(crate) async fn get_block_receipts(
+    number: u64,
+    source: Arc<Source>,
+) -> Result<Vec<TransactionReceipt>, CollectError> {
+    if source.block_receipts_unsupported.load(Ordering::Relaxed) {
+        return get_block_receipts_by_transaction(number, source).await
+    }
+
+    let permit = match source.semaphore.clone() {
+        Some(semaphore) => Some(semaphore.acquire_owned().await),
+        _ => None,
+    };
+    if let Some(limiter) = source.rate_limiter.as_ref() {
+        Arc::clone(limiter).until_ready().await;
+    }
+    let block_receipts =
+        source.provider.get_block_receipts(BlockNumber::Number(number.into())).await;
+    drop(permit);
+
+    match block_receipts {
+        Ok(receipts) => Ok(receipts),
+        Err(e) if is_method_not_found(&e) => {
+            source.block_receipts_unsupported.store(true, Ordering::Relaxed);
+            get_block_receipts_by_transaction(number, source).await
+        }
+        Err(e) => Err(CollectError::ProviderError(e)),
+    }
+}
+
+/// whether error is the json-rpc "method not found" error
+fn is_method_not_found(e: &ProviderError) -> bool {
+    match e.as_error_response() {
+        Some(response) => {
+            let message = response.message.to_lowercase();
+            response.code == -32601 ||
+                (message.contains("method") &&
+                    (message.contains("not found") || message.contains("does not exist")))
+        }
+        None => false,
+    }
+}
+
+async fn get_block_receipts_by_transaction(
+    number: u64,
+    source: Arc<Source>,
+) -> Result<Vec<TransactionReceipt>, CollectError> {
+    let permit = match source.semaphore.clone() {
+        Some(semaphore) => Some(semaphore.acquire_owned().await),
+        _ => None,
+    };
+    if let Some(limiter) = source.rate_limiter.as_ref() {
+        Arc::clone(limiter).until_ready().await;
+    }
+    let block = match source.provider.get_block(number).await {
+        Ok(Some(block)) => block,
+        Ok(None) => return Err(CollectError::CollectError("block not in node".to_string())),
+        Err(e) => return Err(CollectError::ProviderError(e)),
+    };
+    drop(permit);
+
+    let mut tasks = Vec::new();
+    for tx_hash in block.transactions {
+        let provider = source.provider.clone();
+        let semaphore = source.semaphore.clone();
+        let rate_limiter = source.rate_limiter.as_ref().map(Arc::clone);
+        let task = task::spawn(async move {
+            let _permit = match semaphore {
+                Some(semaphore) => Some(Arc::clone(&semaphore).acquire_owned().await),
+                _ => None,
+            };
+            if let Some(limiter) = rate_limiter {
+                Arc::clone(&limiter).until_ready().await;
+            };
+            provider.get_transaction_receipt(tx_hash).await
+        });
+        tasks.push(task);
+    }
+
+    let mut receipts = Vec::new();
+    for task in tasks {
+        match task.await {
+            Ok(Ok(Some(receipt))) => receipts.push(receipt),
+            Ok(Ok(None)) => {
+                return Err(CollectError::CollectError("could not find tx receipt".to_string()))
+            }
+            Ok(Err(e)) => return Err(CollectError::ProviderError(e)),
+            Err(e) => return Err(CollectError::TaskFailed(e)),
+        }
+    }
+    Ok(receipts)
+}
+
+async fn receipts_to_df(
+    mut rx: mpsc::Receiver<Result<Vec<TransactionReceipt>, CollectError>>,
+    schema: &Table,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut block_number: Vec<Option<u32>> = Vec::new();
+    let mut block_hash: Vec<Option<Vec<u8>>> = Vec::new();
+    let mut transaction_index: Vec<u32> = Vec::new();
+    let mut transaction_hash: Vec<Vec<u8>> = Vec::new();
+    let mut from_address: Vec<Vec<u8>> = Vec::new();
+    let mut to_address: Vec<Option<Vec<u8>>> = Vec::new();
+    let mut contract_address: Vec<Option<Vec<u8>>> = Vec::new();
+    let mut status: Vec<Option<u32>> = Vec::new();
+    let mut cumulative_gas_used: Vec<u64> = Vec::new();
+    let mut gas_used: Vec<Option<u64>> = Vec::new();
+    let mut effective_gas_price: Vec<Option<u64>> = Vec::new();
+    let mut transaction_type: Vec<Option<u32>> = Vec::new();
+    let mut logs_bloom: Vec<Vec<u8>> = Vec::new();
+
+    let mut n_rows = 0;
+    while let Some(message) = rx.recv().await {
+        match message {
+            Ok(receipts) => {
+                for receipt in receipts.iter() {
+                    n_rows += 1;
+                    block_number.push(receipt.block_number.map(|x| x.as_u32()));
+                    block_hash.push(receipt.block_hash.map(|x| x.as_bytes().to_vec()));
+                    transaction_index.push(receipt.transaction_index.as_u32());
+                    transaction_hash.push(receipt.transaction_hash.as_bytes().to_vec());
+                    from_address.push(receipt.from.as_bytes().to_vec());
+                    to_address.push(receipt.to.map(|x| x.as_bytes().to_vec()));
+                    contract_address.push(receipt.contract_address.map(|x| x.as_bytes().to_vec()));
+                    status.push(receipt.status.map(|x| x.as_u32()));
+                    cumulative_gas_used.push(receipt.cumulative_gas_used.as_u64());
+                    gas_used.push(receipt.gas_used.map(|x| x.as_u64()));
+                    effective_gas_price.push(receipt.effective_gas_price.map(|x| x.as_u64()));
+                    transaction_type.push(receipt.transaction_type.map(|x| x.as_u32()));
+                    logs_bloom.push(receipt.logs_bloom.0.to_vec());
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    let mut cols = Vec::new();
+    with_series!(cols, "block_number", block_number, schema);
+    with_series_binary!(cols, "block_hash", block_hash, schema);
+    with_series!(cols, "transaction_index", transaction_index, schema);
+    with_series_binary!(cols, "transaction_hash", transaction_hash, schema);
+    with_series_binary!(cols, "from_address", from_address, schema);
+    with_series_binary!(cols, "to_address", to_address, schema);
+    with_series_binary!(cols, "contract_address", contract_address, schema);
+    with_series!(cols, "status", status, schema);
+    with_series!(cols, "cumulative_gas_used", cumulative_gas_used, schema);
+    with_series!(cols, "gas_used", gas_used, schema);
+    with_series!(cols, "effective_gas_price", effective_gas_price, schema);
+    with_series!(cols, "transaction_type", transaction_type, schema);
+    with_series_binary!(cols, "logs_bloom", logs_bloom, schema);
+
+    if schema.has_column("chain_id") {
+        cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+    }
+
+    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+}
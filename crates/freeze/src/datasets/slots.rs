@@ -0,0 +1,156 @@
+use std::{collections::HashMap, sync::Arc};
+
+use ethers::prelude::*;
+use polars::prelude::*;
+use tokio::{sync::mpsc, task};
+
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        conversions::ToVecHex, BlockChunk, CollectError, ColumnType, Dataset, Datatype, RowFilter,
+        Slots, Source, Table,
+    },
+    with_series, with_series_binary,
+};
+
+#[async_trait::async_trait]
+impl Dataset for Slots {
+    fn datatype(&self) -> Datatype {
+        Datatype::Slots
+    }
+
+    fn name(&self) -> &'static str {
+        "slots"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("address", ColumnType::Binary),
+            ("slot", ColumnType::Binary),
+            ("value", ColumnType::Binary),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec!["block_number", "address", "slot", "value"]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["block_number".to_string(), "address".to_string(), "slot".to_string()]
+    }
+
+    async fn collect_block_chunk(
+        &self,
+        chunk: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let pairs = contract_slot_pairs(filter)?;
+        let rx = fetch_slots(chunk, pairs, source).await;
+        slots_to_df(rx, schema, source.chain_id).await
+    }
+}
+
+/// get every (contract, slot) pair to read from the filter
+fn contract_slot_pairs(filter: Option<&RowFilter>) -> Result<Vec<(H160, H256)>, CollectError> {
+    let (contracts, slots) = match filter {
+        Some(RowFilter { address: Some(address), slots: Some(slots), .. }) => {
+            let contracts = match address {
+                ValueOrArray::Value(address) => vec![*address],
+                ValueOrArray::Array(addresses) => addresses.clone(),
+            };
+            (contracts, slots)
+        }
+        _ => {
+            return Err(CollectError::CollectError(
+                "slots requires contracts and slots to read".to_string(),
+            ))
+        }
+    };
+    Ok(contracts
+        .iter()
+        .flat_map(|contract| slots.iter().map(move |slot| (*contract, *slot)))
+        .collect())
+}
+
+async fn fetch_slots(
+    block_chunk: &BlockChunk,
+    pairs: Vec<(H160, H256)>,
+    source: &Source,
+) -> mpsc::Receiver<Result<(u64, H160, H256, H256), CollectError>> {
+    let (tx, rx) = mpsc::channel((block_chunk.numbers().len() * pairs.len()).max(1));
+    let source = Arc::new(source.clone());
+
+    for number in block_chunk.numbers() {
+        for (contract, slot) in pairs.iter() {
+            let (contract, slot) = (*contract, *slot);
+            let tx = tx.clone();
+            let source = Arc::clone(&source);
+            task::spawn(async move {
+                let permit = match source.semaphore.clone() {
+                    Some(semaphore) => Some(semaphore.acquire_owned().await),
+                    _ => None,
+                };
+                if let Some(limiter) = source.rate_limiter.as_ref() {
+                    Arc::clone(limiter).until_ready().await;
+                }
+                let block = BlockId::Number(BlockNumber::Number(number.into()));
+                let result = source
+                    .provider
+                    .get_storage_at(contract, slot, Some(block))
+                    .await
+                    .map(|value| (number, contract, slot, value))
+                    .map_err(CollectError::ProviderError);
+                drop(permit);
+                match tx.send(result).await {
+                    Ok(_) => {}
+                    Err(tokio::sync::mpsc::error::SendError(_e)) => {
+                        eprintln!("send error, try using a rate limit with --requests-per-second or limiting max concurrency with --max-concurrent-requests");
+                        std::process::exit(1)
+                    }
+                }
+            });
+        }
+    }
+    rx
+}
+
+async fn slots_to_df(
+    mut rx: mpsc::Receiver<Result<(u64, H160, H256, H256), CollectError>>,
+    schema: &Table,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut address: Vec<Vec<u8>> = Vec::new();
+    let mut slot: Vec<Vec<u8>> = Vec::new();
+    let mut value: Vec<Vec<u8>> = Vec::new();
+
+    let mut n_rows = 0;
+    while let Some(message) = rx.recv().await {
+        match message {
+            Ok((number, contract, location, contents)) => {
+                n_rows += 1;
+                block_number.push(number as u32);
+                address.push(contract.as_bytes().to_vec());
+                slot.push(location.as_bytes().to_vec());
+                value.push(contents.as_bytes().to_vec());
+            }
+            Err(e) => return Err(CollectError::RPCError(e.to_string())),
+        }
+    }
+
+    let mut cols = Vec::new();
+    with_series!(cols, "block_number", block_number, schema);
+    with_series_binary!(cols, "address", address, schema);
+    with_series_binary!(cols, "slot", slot, schema);
+    with_series_binary!(cols, "value", value, schema);
+
+    if schema.has_column("chain_id") {
+        cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+    }
+
+    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+}
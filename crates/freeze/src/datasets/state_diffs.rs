@@ -1,14 +1,15 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use ethers::prelude::*;
 use polars::prelude::*;
 use tokio::sync::mpsc;
 
+use super::{traces, transactions};
 use crate::{
     dataframes::SortableDataFrame,
     types::{
-        conversions::ToVecHex, BlockChunk, ChunkData, CollectError, ColumnType, Datatype,
-        MultiDataset, RowFilter, Source, StateDiffs, Table,
+        conversions::ToVecHex, BlockChunk, Chunk, ChunkData, CollectError, ColumnType, Datatype,
+        MultiDataset, RowFilter, Source, StateDiffs, Table, TraceBackend, TransactionChunk,
     },
     with_series, with_series_binary,
 };
@@ -30,24 +31,50 @@ impl MultiDataset for StateDiffs {
         chunk: &BlockChunk,
         source: &Source,
         schemas: HashMap<Datatype, Table>,
-        _filter: HashMap<Datatype, RowFilter>,
+        filter: HashMap<Datatype, RowFilter>,
     ) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
         let rx = fetch_state_diffs(chunk, source).await;
-        state_diffs_to_df(rx, &schemas, source.chain_id).await
+        state_diffs_to_df(rx, &schemas, &filter, source.chain_id).await
+    }
+
+    async fn collect_transaction_chunk(
+        &self,
+        chunk: &TransactionChunk,
+        source: &Source,
+        schemas: HashMap<Datatype, Table>,
+        filter: HashMap<Datatype, RowFilter>,
+    ) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
+        let hashes = transactions::transaction_hashes(chunk)?;
+        let rx = fetch_transaction_state_diffs(&hashes, source).await;
+        state_diffs_to_df(rx, &schemas, &filter, source.chain_id).await
     }
 }
 
+/// traces of consecutive transactions of a block, along with the block number and the index of
+/// the first transaction
+pub(crate) type BlockTraces<T> = (u32, u32, Result<Vec<T>, CollectError>);
+
 pub(crate) async fn collect_single(
     datatype: &Datatype,
-    chunk: &BlockChunk,
+    chunk: &Chunk,
     source: &Source,
     schema: &Table,
-    _filter: Option<&RowFilter>,
+    filter: Option<&RowFilter>,
 ) -> Result<DataFrame, CollectError> {
-    let rx = fetch_state_diffs(chunk, source).await;
+    let rx = match chunk {
+        Chunk::Block(chunk) => fetch_state_diffs(chunk, source).await,
+        Chunk::Transaction(chunk) => {
+            fetch_transaction_state_diffs(&transactions::transaction_hashes(chunk)?, source).await
+        }
+        Chunk::Address(..) => return Err(CollectError::CollectError("invalid chunk".to_string())),
+    };
     let mut schemas: HashMap<Datatype, Table> = HashMap::new();
     schemas.insert(*datatype, schema.clone());
-    let dfs = state_diffs_to_df(rx, &schemas, source.chain_id).await;
+    let mut filters: HashMap<Datatype, RowFilter> = HashMap::new();
+    if let Some(filter) = filter {
+        filters.insert(*datatype, filter.clone());
+    }
+    let dfs = state_diffs_to_df(rx, &schemas, &filters, source.chain_id).await;
 
     // get single df out of result
     let df = match dfs {
@@ -65,7 +92,7 @@ pub(crate) async fn fetch_block_traces(
     block_chunk: &BlockChunk,
     trace_types: &[TraceType],
     source: &Source,
-) -> mpsc::Receiver<(u32, Result<Vec<BlockTrace>, CollectError>)> {
+) -> mpsc::Receiver<BlockTraces<BlockTrace>> {
     let (tx, rx) = mpsc::channel(block_chunk.size() as usize);
     for number in block_chunk.numbers() {
         let tx = tx.clone();
@@ -85,7 +112,7 @@ pub(crate) async fn fetch_block_traces(
                 .trace_replay_block_transactions(BlockNumber::Number(number.into()), trace_types)
                 .await
                 .map_err(CollectError::ProviderError);
-            match tx.send((number as u32, result)).await {
+            match tx.send((number as u32, 0, result)).await {
                 Ok(_) => {}
                 Err(tokio::sync::mpsc::error::SendError(_e)) => {
                     eprintln!("send error, try using a rate limit with --requests-per-second or limiting max concurrency with --max-concurrent-requests");
@@ -98,16 +125,220 @@ pub(crate) async fn fetch_block_traces(
     rx
 }
 
+/// replay transactions one at a time with trace_replayTransaction
+pub(crate) async fn fetch_transaction_block_traces(
+    hashes: &[H256],
+    trace_types: &[TraceType],
+    source: &Source,
+) -> mpsc::Receiver<BlockTraces<BlockTrace>> {
+    let trace_types = trace_types.to_vec();
+    transactions::fetch_by_hash(hashes, source, move |source, hash| {
+        let trace_types = trace_types.clone();
+        async move {
+            let (_, number, index) =
+                match transactions::get_mined_transaction(&source.provider, hash).await {
+                    Ok(mined) => mined,
+                    Err(e) => return (0, 0, Err(e)),
+                };
+            if let Some(limiter) = source.rate_limiter.as_ref() {
+                Arc::clone(limiter).until_ready().await;
+            }
+            let result = source
+                .provider
+                .trace_replay_transaction(hash, trace_types)
+                .await
+                .map(|mut trace| {
+                    trace.transaction_hash = Some(hash);
+                    vec![trace]
+                })
+                .map_err(CollectError::ProviderError);
+            (number as u32, index as u32, result)
+        }
+    })
+    .await
+}
+
 pub(crate) async fn fetch_state_diffs(
     block_chunk: &BlockChunk,
     source: &Source,
-) -> mpsc::Receiver<(u32, Result<Vec<BlockTrace>, CollectError>)> {
-    fetch_block_traces(block_chunk, &[TraceType::StateDiff], source).await
+) -> mpsc::Receiver<BlockTraces<BlockTrace>> {
+    match source.trace_backend {
+        TraceBackend::Parity => {
+            fetch_block_traces(block_chunk, &[TraceType::StateDiff], source).await
+        }
+        TraceBackend::Geth => fetch_geth_state_diffs(block_chunk, source).await,
+    }
+}
+
+async fn fetch_transaction_state_diffs(
+    hashes: &[H256],
+    source: &Source,
+) -> mpsc::Receiver<BlockTraces<BlockTrace>> {
+    match source.trace_backend {
+        TraceBackend::Parity => {
+            fetch_transaction_block_traces(hashes, &[TraceType::StateDiff], source).await
+        }
+        TraceBackend::Geth => fetch_geth_transaction_state_diffs(hashes, source).await,
+    }
+}
+
+/// fetch state diffs using geth's prestateTracer in diff mode
+async fn fetch_geth_state_diffs(
+    block_chunk: &BlockChunk,
+    source: &Source,
+) -> mpsc::Receiver<BlockTraces<BlockTrace>> {
+    let (tx, rx) = mpsc::channel(block_chunk.size() as usize);
+    for number in block_chunk.numbers() {
+        let tx = tx.clone();
+        let provider = source.provider.clone();
+        let semaphore = source.semaphore.clone();
+        let rate_limiter = source.rate_limiter.as_ref().map(Arc::clone);
+        tokio::spawn(async move {
+            let _permit = match semaphore {
+                Some(semaphore) => Some(Arc::clone(&semaphore).acquire_owned().await),
+                _ => None,
+            };
+            if let Some(limiter) = &rate_limiter {
+                Arc::clone(limiter).until_ready().await;
+            }
+            let options = geth_state_diff_options();
+            let result =
+                traces::geth_debug_trace_block(&provider, rate_limiter, number, options).await;
+            let result = result.and_then(|(block, tx_traces)| {
+                block
+                    .transactions
+                    .iter()
+                    .zip(tx_traces)
+                    .map(|(tx_hash, tx_trace)| geth_state_diff_trace(tx_trace, *tx_hash))
+                    .collect()
+            });
+            match tx.send((number as u32, 0, result)).await {
+                Ok(_) => {}
+                Err(tokio::sync::mpsc::error::SendError(_e)) => {
+                    eprintln!("send error, try using a rate limit with --requests-per-second or limiting max concurrency with --max-concurrent-requests");
+                    std::process::exit(1)
+                }
+            }
+        });
+    }
+
+    rx
+}
+
+/// fetch state diffs of individual transactions using geth's prestateTracer in diff mode
+async fn fetch_geth_transaction_state_diffs(
+    hashes: &[H256],
+    source: &Source,
+) -> mpsc::Receiver<BlockTraces<BlockTrace>> {
+    transactions::fetch_by_hash(hashes, source, |source, hash| async move {
+        let (_, number, index) =
+            match transactions::get_mined_transaction(&source.provider, hash).await {
+                Ok(mined) => mined,
+                Err(e) => return (0, 0, Err(e)),
+            };
+        if let Some(limiter) = source.rate_limiter.as_ref() {
+            Arc::clone(limiter).until_ready().await;
+        }
+        let result = source
+            .provider
+            .debug_trace_transaction(hash, geth_state_diff_options())
+            .await
+            .map_err(CollectError::ProviderError)
+            .and_then(|tx_trace| geth_state_diff_trace(tx_trace, hash))
+            .map(|trace| vec![trace]);
+        (number as u32, index as u32, result)
+    })
+    .await
+}
+
+fn geth_state_diff_options() -> GethDebugTracingOptions {
+    GethDebugTracingOptions {
+        tracer: Some(GethDebugTracerType::BuiltInTracer(
+            GethDebugBuiltInTracerType::PreStateTracer,
+        )),
+        tracer_config: Some(GethDebugTracerConfig::BuiltInTracer(
+            GethDebugBuiltInTracerConfig::PreStateTracer(PreStateConfig { diff_mode: Some(true) }),
+        )),
+        ..Default::default()
+    }
+}
+
+/// convert the prestateTracer result of a transaction into a parity-style state diff trace
+fn geth_state_diff_trace(tx_trace: GethTrace, tx_hash: H256) -> Result<BlockTrace, CollectError> {
+    match tx_trace {
+        GethTrace::Known(GethTraceFrame::PreStateTracer(PreStateFrame::Diff(diff))) => {
+            Ok(BlockTrace {
+                output: Bytes::default(),
+                trace: None,
+                vm_trace: None,
+                state_diff: Some(geth_diff_to_state_diff(diff)),
+                transaction_hash: Some(tx_hash),
+            })
+        }
+        _ => Err(CollectError::CollectError("invalid prestateTracer result".to_string())),
+    }
+}
+
+/// convert geth pre and post account states into parity-style account diffs
+///
+/// accounts present only in post were created and accounts present only in pre were deleted,
+/// every field of such accounts is born or died, and storage slots missing from post were zeroed
+fn geth_diff_to_state_diff(diff: DiffMode) -> StateDiff {
+    let addresses: BTreeSet<&H160> = diff.pre.keys().chain(diff.post.keys()).collect();
+    let empty = AccountState::default();
+    let mut state_diff = BTreeMap::new();
+    for address in addresses.into_iter() {
+        let born = !diff.pre.contains_key(address);
+        let died = !diff.post.contains_key(address);
+        let pre = diff.pre.get(address).unwrap_or(&empty);
+        let post = diff.post.get(address).unwrap_or(&empty);
+
+        let pre_storage = pre.storage.clone().unwrap_or_default();
+        let post_storage = post.storage.clone().unwrap_or_default();
+        let mut storage = BTreeMap::new();
+        for slot in pre_storage.keys().chain(post_storage.keys()) {
+            let from = pre_storage.get(slot).copied().unwrap_or_default();
+            let to = post_storage.get(slot).copied().unwrap_or_default();
+            storage.insert(*slot, geth_value_diff(Some(from), Some(to), born, died));
+        }
+
+        let account_diff = AccountDiff {
+            balance: geth_value_diff(pre.balance, post.balance, born, died),
+            nonce: geth_value_diff(pre.nonce, post.nonce, born, died),
+            code: geth_value_diff(
+                pre.code.as_ref().and_then(|code| code.parse::<Bytes>().ok()),
+                post.code.as_ref().and_then(|code| code.parse::<Bytes>().ok()),
+                born,
+                died,
+            ),
+            storage,
+        };
+        state_diff.insert(*address, account_diff);
+    }
+    StateDiff(state_diff)
+}
+
+/// geth omits unchanged fields from post, so a missing post value means no change
+fn geth_value_diff<T: PartialEq + Default>(
+    pre: Option<T>,
+    post: Option<T>,
+    born: bool,
+    died: bool,
+) -> Diff<T> {
+    match (pre, post) {
+        (_, to) if born => Diff::Born(to.unwrap_or_default()),
+        (from, _) if died => Diff::Died(from.unwrap_or_default()),
+        (Some(from), Some(to)) if from == to => Diff::Same,
+        (Some(from), Some(to)) => Diff::Changed(ChangedType { from, to }),
+        (None, Some(to)) => Diff::Changed(ChangedType { from: T::default(), to }),
+        (_, None) => Diff::Same,
+    }
 }
 
 async fn state_diffs_to_df(
-    mut rx: mpsc::Receiver<(u32, Result<Vec<BlockTrace>, CollectError>)>,
+    mut rx: mpsc::Receiver<BlockTraces<BlockTrace>>,
     schemas: &HashMap<Datatype, Table>,
+    filters: &HashMap<Datatype, RowFilter>,
     chain_id: u64,
 ) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
     let include_storage = schemas.contains_key(&Datatype::StorageDiffs);
@@ -115,6 +346,11 @@ async fn state_diffs_to_df(
     let include_nonce = schemas.contains_key(&Datatype::NonceDiffs);
     let include_code = schemas.contains_key(&Datatype::CodeDiffs);
 
+    let storage_filter = DiffFilter::new(filters.get(&Datatype::StorageDiffs));
+    let balance_filter = DiffFilter::new(filters.get(&Datatype::BalanceDiffs));
+    let nonce_filter = DiffFilter::new(filters.get(&Datatype::NonceDiffs));
+    let code_filter = DiffFilter::new(filters.get(&Datatype::CodeDiffs));
+
     let capacity = 0;
 
     // storage
@@ -185,9 +421,9 @@ async fn state_diffs_to_df(
     let mut n_rows = 0;
     while let Some(message) = rx.recv().await {
         match message {
-            (block_num, Ok(blocks_traces)) => {
+            (block_num, first_index, Ok(blocks_traces)) => {
                 for (t_index, ts) in blocks_traces.iter().enumerate() {
-                    let t_index = t_index as u32;
+                    let t_index = first_index + t_index as u32;
                     if let (Some(tx), Some(StateDiff(state_diff))) =
                         (ts.transaction_hash, &ts.state_diff)
                     {
@@ -195,8 +431,11 @@ async fn state_diffs_to_df(
                             n_rows += n_rows;
 
                             // storage
-                            if include_storage {
+                            if include_storage && storage_filter.has_address(addr) {
                                 for (s, diff) in addr_diff.storage.iter() {
+                                    if !storage_filter.has_slot(s) {
+                                        continue
+                                    }
                                     let (from, to) = match diff {
                                         Diff::Same => (H256::zero(), H256::zero()),
                                         Diff::Born(value) => (H256::zero(), *value),
@@ -228,7 +467,7 @@ async fn state_diffs_to_df(
                             }
 
                             // balance
-                            if include_balance {
+                            if include_balance && balance_filter.has_address(addr) {
                                 let (from, to) = match addr_diff.balance {
                                     Diff::Same => ("0".to_string(), "0".to_string()),
                                     Diff::Born(value) => ("0".to_string(), value.to_string()),
@@ -258,7 +497,7 @@ async fn state_diffs_to_df(
                             }
 
                             // nonce
-                            if include_nonce {
+                            if include_nonce && nonce_filter.has_address(addr) {
                                 let (from, to) = match addr_diff.nonce {
                                     Diff::Same => (0u64, 0u64),
                                     Diff::Born(value) => (0u64, value.as_u64()),
@@ -288,7 +527,7 @@ async fn state_diffs_to_df(
                             }
 
                             // code
-                            if include_code {
+                            if include_code && code_filter.has_address(addr) {
                                 let (from, to) = match &addr_diff.code {
                                     Diff::Same => (
                                         H256::zero().as_bytes().to_vec(),
@@ -405,6 +644,31 @@ async fn state_diffs_to_df(
     Ok(dfs)
 }
 
+/// addresses and storage slots that diffs are filtered by
+struct DiffFilter {
+    addresses: Option<HashSet<H160>>,
+    slots: Option<HashSet<H256>>,
+}
+
+impl DiffFilter {
+    fn new(filter: Option<&RowFilter>) -> Self {
+        let addresses = filter.and_then(|f| f.address.as_ref()).map(|address| match address {
+            ValueOrArray::Value(address) => [*address].into_iter().collect(),
+            ValueOrArray::Array(addresses) => addresses.iter().copied().collect(),
+        });
+        let slots = filter.and_then(|f| f.slots.as_ref()).map(|s| s.iter().copied().collect());
+        Self { addresses, slots }
+    }
+
+    fn has_address(&self, address: &H160) -> bool {
+        self.addresses.as_ref().map(|addresses| addresses.contains(address)).unwrap_or(true)
+    }
+
+    fn has_slot(&self, slot: &H256) -> bool {
+        self.slots.as_ref().map(|slots| slots.contains(slot)).unwrap_or(true)
+    }
+}
+
 fn included(
     schemas: &HashMap<Datatype, Table>,
     datatype: Datatype,
@@ -416,3 +680,81 @@ fn included(
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(balance: u64, nonce: u64, storage: &[(u64, u64)]) -> AccountState {
+        let storage = storage
+            .iter()
+            .map(|(slot, value)| (H256::from_low_u64_be(*slot), H256::from_low_u64_be(*value)))
+            .collect();
+        AccountState {
+            balance: Some(U256::from(balance)),
+            nonce: Some(U256::from(nonce)),
+            code: None,
+            storage: Some(storage),
+        }
+    }
+
+    fn state_diff(pre: Vec<(u64, AccountState)>, post: Vec<(u64, AccountState)>) -> StateDiff {
+        let accounts = |states: Vec<(u64, AccountState)>| {
+            states.into_iter().map(|(address, state)| (H160::from_low_u64_be(address), state))
+        };
+        let diff = DiffMode { pre: accounts(pre).collect(), post: accounts(post).collect() };
+        geth_diff_to_state_diff(diff)
+    }
+
+    fn slot(value: u64) -> H256 {
+        H256::from_low_u64_be(value)
+    }
+
+    #[test]
+    fn geth_created_account_is_born() {
+        let StateDiff(diff) = state_diff(vec![], vec![(1, account(5, 1, &[(2, 3)]))]);
+        let account_diff = &diff[&H160::from_low_u64_be(1)];
+        assert_eq!(account_diff.balance, Diff::Born(U256::from(5)));
+        assert_eq!(account_diff.nonce, Diff::Born(U256::from(1)));
+        assert_eq!(account_diff.code, Diff::Born(Bytes::default()));
+        assert_eq!(account_diff.storage[&slot(2)], Diff::Born(slot(3)));
+    }
+
+    #[test]
+    fn geth_self_destructed_account_dies() {
+        let StateDiff(diff) = state_diff(vec![(1, account(5, 1, &[(2, 3)]))], vec![]);
+        let account_diff = &diff[&H160::from_low_u64_be(1)];
+        assert_eq!(account_diff.balance, Diff::Died(U256::from(5)));
+        assert_eq!(account_diff.nonce, Diff::Died(U256::from(1)));
+        assert_eq!(account_diff.code, Diff::Died(Bytes::default()));
+        assert_eq!(account_diff.storage[&slot(2)], Diff::Died(slot(3)));
+    }
+
+    #[test]
+    fn geth_slot_missing_from_post_is_zeroed() {
+        let pre = account(5, 1, &[(2, 3), (4, 5)]);
+        let post =
+            AccountState { storage: Some([(slot(4), slot(6))].into()), ..Default::default() };
+        let StateDiff(diff) = state_diff(vec![(1, pre)], vec![(1, post)]);
+        let account_diff = &diff[&H160::from_low_u64_be(1)];
+        let zeroed = Diff::Changed(ChangedType { from: slot(3), to: H256::zero() });
+        assert_eq!(account_diff.storage[&slot(2)], zeroed);
+        let changed = Diff::Changed(ChangedType { from: slot(5), to: slot(6) });
+        assert_eq!(account_diff.storage[&slot(4)], changed);
+        assert_eq!(account_diff.balance, Diff::Same);
+        assert_eq!(account_diff.nonce, Diff::Same);
+    }
+
+    #[test]
+    fn geth_balance_only_change() {
+        let pre = account(5, 1, &[]);
+        let post = AccountState { balance: Some(U256::from(7)), ..Default::default() };
+        let StateDiff(diff) = state_diff(vec![(1, pre)], vec![(1, post)]);
+        let account_diff = &diff[&H160::from_low_u64_be(1)];
+        let changed = Diff::Changed(ChangedType { from: U256::from(5), to: U256::from(7) });
+        assert_eq!(account_diff.balance, changed);
+        assert_eq!(account_diff.nonce, Diff::Same);
+        assert_eq!(account_diff.code, Diff::Same);
+        assert!(account_diff.storage.is_empty());
+    }
+}
@@ -6,9 +6,11 @@ use tokio::{sync::mpsc, task};
 
 use crate::{
     dataframes::SortableDataFrame,
+    datasets::transactions,
     types::{
-        conversions::ToVecHex, BlockChunk, CollectError, ColumnType, Dataset, Datatype, RowFilter,
-        Source, Table, Traces,
+        conversions::{self, ToVecHex},
+        BlockChunk, CollectError, ColumnType, Dataset, Datatype, RateLimiter, RowFilter, Source,
+        Table, TraceBackend, Traces, TransactionChunk,
     },
     with_series, with_series_binary,
 };
@@ -45,6 +47,10 @@ impl Dataset for Traces {
             ("block_number", ColumnType::UInt32),
             ("block_hash", ColumnType::Binary),
             ("error", ColumnType::String),
+            ("function_selector", ColumnType::Binary),
+            ("function_name", ColumnType::String),
+            ("function_inputs", ColumnType::String),
+            ("function_outputs", ColumnType::String),
             ("chain_id", ColumnType::UInt64),
         ])
     }
@@ -83,14 +89,33 @@ impl Dataset for Traces {
         chunk: &BlockChunk,
         source: &Source,
         schema: &Table,
-        _filter: Option<&RowFilter>,
+        filter: Option<&RowFilter>,
     ) -> Result<DataFrame, CollectError> {
-        let rx = fetch_traces(chunk, source).await;
-        traces_to_df(rx, schema, source.chain_id).await
+        let from_address = filter.and_then(|f| f.from_address.clone());
+        let to_address = filter.and_then(|f| f.to_address.clone());
+        let rx = match (&from_address, &to_address, source.trace_backend) {
+            (None, None, _) | (_, _, TraceBackend::Geth) => fetch_traces(chunk, source).await,
+            (_, _, TraceBackend::Parity) => {
+                fetch_filtered_traces(chunk, source, from_address, to_address).await
+            }
+        };
+        traces_to_df(rx, schema, filter, source.chain_id).await
+    }
+
+    async fn collect_transaction_chunk(
+        &self,
+        chunk: &TransactionChunk,
+        source: &Source,
+        schema: &Table,
+        filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let hashes = transactions::transaction_hashes(chunk)?;
+        let rx = fetch_transaction_traces(&hashes, source).await;
+        traces_to_df(rx, schema, filter, source.chain_id).await
     }
 }
 
-async fn fetch_traces(
+pub(crate) async fn fetch_traces(
     block_chunk: &BlockChunk,
     source: &Source,
 ) -> mpsc::Receiver<Result<Vec<Trace>, CollectError>> {
@@ -101,6 +126,61 @@ async fn fetch_traces(
         let provider = source.provider.clone();
         let semaphore = source.semaphore.clone();
         let rate_limiter = source.rate_limiter.as_ref().map(Arc::clone);
+        let trace_backend = source.trace_backend;
+        task::spawn(async move {
+            let _permit = match semaphore {
+                Some(semaphore) => Some(Arc::clone(&semaphore).acquire_owned().await),
+                _ => None,
+            };
+            if let Some(limiter) = &rate_limiter {
+                Arc::clone(limiter).until_ready().await;
+            }
+            let result = match trace_backend {
+                TraceBackend::Parity => provider
+                    .trace_block(BlockNumber::Number(number.into()))
+                    .await
+                    .map_err(CollectError::ProviderError),
+                TraceBackend::Geth => geth_trace_block(&provider, rate_limiter, number).await,
+            };
+            match tx.send(result).await {
+                Ok(_) => {}
+                Err(tokio::sync::mpsc::error::SendError(_e)) => {
+                    eprintln!("send error, try using a rate limit with --requests-per-second or limiting max concurrency with --max-concurrent-requests");
+                    std::process::exit(1)
+                }
+            }
+        });
+    }
+    rx
+}
+
+/// fetch traces of a block range with trace_filter, selecting traces by sender and receiver
+async fn fetch_filtered_traces(
+    block_chunk: &BlockChunk,
+    source: &Source,
+    from_address: Option<Vec<H160>>,
+    to_address: Option<Vec<H160>>,
+) -> mpsc::Receiver<Result<Vec<Trace>, CollectError>> {
+    let request_chunks = block_chunk.to_log_filter_options(&source.inner_request_size);
+    let (tx, rx) = mpsc::channel(request_chunks.len().max(1));
+
+    for request_chunk in request_chunks.iter() {
+        let tx = tx.clone();
+        let provider = source.provider.clone();
+        let semaphore = source.semaphore.clone();
+        let rate_limiter = source.rate_limiter.as_ref().map(Arc::clone);
+        let mut trace_filter = match request_chunk {
+            FilterBlockOption::Range { from_block: Some(from), to_block: Some(to) } => {
+                TraceFilter::default().from_block(*from).to_block(*to)
+            }
+            _ => TraceFilter::default(),
+        };
+        if let Some(from_address) = &from_address {
+            trace_filter = trace_filter.from_address(from_address.clone());
+        }
+        if let Some(to_address) = &to_address {
+            trace_filter = trace_filter.to_address(to_address.clone());
+        }
         task::spawn(async move {
             let _permit = match semaphore {
                 Some(semaphore) => Some(Arc::clone(&semaphore).acquire_owned().await),
@@ -109,10 +189,8 @@ async fn fetch_traces(
             if let Some(limiter) = rate_limiter {
                 Arc::clone(&limiter).until_ready().await;
             }
-            let result = provider
-                .trace_block(BlockNumber::Number(number.into()))
-                .await
-                .map_err(CollectError::ProviderError);
+            let result =
+                provider.trace_filter(trace_filter).await.map_err(CollectError::ProviderError);
             match tx.send(result).await {
                 Ok(_) => {}
                 Err(tokio::sync::mpsc::error::SendError(_e)) => {
@@ -125,6 +203,213 @@ async fn fetch_traces(
     rx
 }
 
+/// trace a block with geth's callTracer and flatten its call frames into parity-style traces
+async fn geth_trace_block(
+    provider: &Provider<Http>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    number: u64,
+) -> Result<Vec<Trace>, CollectError> {
+    let options = GethDebugTracingOptions {
+        tracer: Some(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::CallTracer)),
+        ..Default::default()
+    };
+    let (block, tx_traces) =
+        geth_debug_trace_block(provider, rate_limiter, number, options).await?;
+    let block_hash = match block.hash {
+        Some(block_hash) => block_hash,
+        None => return Err(CollectError::CollectError("block has no hash".to_string())),
+    };
+
+    let mut traces = Vec::new();
+    for (tx_pos, (tx_hash, tx_trace)) in block.transactions.iter().zip(tx_traces).enumerate() {
+        traces.extend(geth_call_traces(tx_trace, number, block_hash, *tx_hash, tx_pos)?);
+    }
+    Ok(traces)
+}
+
+pub(crate) async fn fetch_transaction_traces(
+    hashes: &[H256],
+    source: &Source,
+) -> mpsc::Receiver<Result<Vec<Trace>, CollectError>> {
+    transactions::fetch_by_hash(hashes, source, |source, hash| async move {
+        match source.trace_backend {
+            TraceBackend::Parity => {
+                source.provider.trace_transaction(hash).await.map_err(CollectError::ProviderError)
+            }
+            TraceBackend::Geth => {
+                geth_trace_transaction(&source.provider, source.rate_limiter.clone(), hash).await
+            }
+        }
+    })
+    .await
+}
+
+/// trace a transaction with geth's callTracer and flatten its call frames into parity-style traces
+async fn geth_trace_transaction(
+    provider: &Provider<Http>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    hash: H256,
+) -> Result<Vec<Trace>, CollectError> {
+    let options = GethDebugTracingOptions {
+        tracer: Some(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::CallTracer)),
+        ..Default::default()
+    };
+    let (transaction, number, index) = transactions::get_mined_transaction(provider, hash).await?;
+    if let Some(limiter) = rate_limiter {
+        Arc::clone(&limiter).until_ready().await;
+    }
+    let tx_trace = provider
+        .debug_trace_transaction(hash, options)
+        .await
+        .map_err(CollectError::ProviderError)?;
+    let block_hash = match transaction.block_hash {
+        Some(block_hash) => block_hash,
+        None => return Err(CollectError::CollectError("block has no hash".to_string())),
+    };
+    geth_call_traces(tx_trace, number, block_hash, hash, index as usize)
+}
+
+/// flatten the callTracer result of a transaction into parity-style traces
+fn geth_call_traces(
+    tx_trace: GethTrace,
+    number: u64,
+    block_hash: H256,
+    tx_hash: H256,
+    tx_pos: usize,
+) -> Result<Vec<Trace>, CollectError> {
+    let frame = match tx_trace {
+        GethTrace::Known(GethTraceFrame::CallTracer(frame)) => frame,
+        _ => return Err(CollectError::CollectError("invalid callTracer result".to_string())),
+    };
+    let mut frames = Vec::new();
+    flatten_call_frames(frame, Vec::new(), &mut frames);
+    let mut traces = Vec::new();
+    for (trace_address, subtraces, frame) in frames.into_iter() {
+        let (action, action_type, result) = call_frame_to_action(&frame);
+        traces.push(Trace {
+            action,
+            result,
+            trace_address,
+            subtraces,
+            transaction_position: Some(tx_pos),
+            transaction_hash: Some(tx_hash),
+            block_number: number,
+            block_hash,
+            action_type,
+            error: frame.error,
+        });
+    }
+    Ok(traces)
+}
+
+/// trace each transaction of a block with debug_traceBlockByNumber
+///
+/// geth results do not include transaction hashes, so the block is fetched alongside them
+pub(crate) async fn geth_debug_trace_block(
+    provider: &Provider<Http>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    number: u64,
+    options: GethDebugTracingOptions,
+) -> Result<(Block<TxHash>, Vec<GethTrace>), CollectError> {
+    let block = match provider.get_block(number).await {
+        Ok(Some(block)) => block,
+        Ok(None) => return Err(CollectError::CollectError("block not in node".to_string())),
+        Err(e) => return Err(CollectError::ProviderError(e)),
+    };
+    if let Some(limiter) = rate_limiter {
+        Arc::clone(&limiter).until_ready().await;
+    }
+    let tx_traces = provider
+        .debug_trace_block_by_number(Some(BlockNumber::Number(number.into())), options)
+        .await
+        .map_err(CollectError::ProviderError)?;
+    if tx_traces.len() != block.transactions.len() {
+        return Err(CollectError::CollectError("wrong number of transaction traces".to_string()))
+    }
+    Ok((block, tx_traces))
+}
+
+/// flatten nested call frames depth-first, along with their trace address and subtrace count
+fn flatten_call_frames(
+    mut frame: CallFrame,
+    trace_address: Vec<usize>,
+    frames: &mut Vec<(Vec<usize>, usize, CallFrame)>,
+) {
+    let calls = frame.calls.take().unwrap_or_default();
+    frames.push((trace_address.clone(), calls.len(), frame));
+    for (index, call) in calls.into_iter().enumerate() {
+        let mut child_address = trace_address.clone();
+        child_address.push(index);
+        flatten_call_frames(call, child_address, frames);
+    }
+}
+
+/// convert a call frame to a parity-style action, frames of unknown type become calls of type none
+fn call_frame_to_action(frame: &CallFrame) -> (Action, ActionType, Option<Res>) {
+    let to = frame.to.as_ref().and_then(|to| to.as_address()).copied().unwrap_or_default();
+    let value = frame.value.unwrap_or_default();
+    let output = frame.output.clone().unwrap_or_default();
+    let call_type = match frame.typ.as_str() {
+        "CALL" => CallType::Call,
+        "CALLCODE" => CallType::CallCode,
+        "DELEGATECALL" => CallType::DelegateCall,
+        "STATICCALL" => CallType::StaticCall,
+        "CREATE" | "CREATE2" => {
+            let action =
+                Create { from: frame.from, value, gas: frame.gas, init: frame.input.clone() };
+            let result = match frame.error {
+                Some(_) => None,
+                None => Some(Res::Create(CreateResult {
+                    gas_used: frame.gas_used,
+                    code: output,
+                    address: to,
+                })),
+            };
+            return (Action::Create(action), ActionType::Create, result)
+        }
+        "SELFDESTRUCT" => {
+            let action = Suicide { address: frame.from, refund_address: to, balance: value };
+            return (Action::Suicide(action), ActionType::Suicide, None)
+        }
+        _ => CallType::None,
+    };
+    let action =
+        Call { from: frame.from, to, value, gas: frame.gas, input: frame.input.clone(), call_type };
+    let result = match frame.error {
+        Some(_) => None,
+        None => Some(Res::Call(CallResult { gas_used: frame.gas_used, output })),
+    };
+    (Action::Call(action), ActionType::Call, result)
+}
+
+/// whether a trace matches sender and receiver filters, using the same rules as trace_filter
+fn trace_matches(
+    trace: &Trace,
+    from_address: Option<&[H160]>,
+    to_address: Option<&[H160]>,
+) -> bool {
+    let (from, to) = match (&trace.action, &trace.result) {
+        (Action::Call(action), _) => (Some(action.from), Some(action.to)),
+        (Action::Create(action), Some(Res::Create(result))) => {
+            (Some(action.from), Some(result.address))
+        }
+        (Action::Create(action), _) => (Some(action.from), None),
+        (Action::Suicide(action), _) => (Some(action.address), Some(action.refund_address)),
+        (Action::Reward(action), _) => (None, Some(action.author)),
+    };
+    let matches = |filter: Option<&[H160]>, address: Option<H160>| match (filter, address) {
+        (None, _) => true,
+        (Some(filter), Some(address)) => filter.contains(&address),
+        (Some(_), None) => false,
+    };
+    matches(from_address, from) && matches(to_address, to)
+}
+
+/// whether trace is nested beneath one of the given failed traces
+pub(crate) fn has_failed_parent(trace_address: &[usize], failed: &[Vec<usize>]) -> bool {
+    failed.iter().any(|parent| trace_address.starts_with(parent))
+}
+
 fn reward_type_to_string(reward_type: &RewardType) -> String {
     match reward_type {
         RewardType::Block => "reward".to_string(),
@@ -156,8 +441,13 @@ fn action_call_type_to_string(action_call_type: &CallType) -> String {
 async fn traces_to_df(
     mut rx: mpsc::Receiver<Result<Vec<Trace>, CollectError>>,
     schema: &Table,
+    filter: Option<&RowFilter>,
     chain_id: u64,
 ) -> Result<DataFrame, CollectError> {
+    let functions = filter.and_then(|f| f.functions.as_deref()).unwrap_or_default();
+    let functions = conversions::function_selectors(functions);
+    let from_address = filter.and_then(|f| f.from_address.as_deref());
+    let to_address = filter.and_then(|f| f.to_address.as_deref());
     let include_action_from = schema.has_column("action_from");
     let include_action_to = schema.has_column("action_to");
     let include_action_value = schema.has_column("action_value");
@@ -178,6 +468,10 @@ async fn traces_to_df(
     let include_block_number = schema.has_column("block_number");
     let include_block_hash = schema.has_column("block_hash");
     let include_error = schema.has_column("error");
+    let include_function_selector = schema.has_column("function_selector");
+    let include_function_name = schema.has_column("function_name");
+    let include_function_inputs = schema.has_column("function_inputs");
+    let include_function_outputs = schema.has_column("function_outputs");
 
     let capacity = 0;
     let mut action_from: Vec<Option<Vec<u8>>> = Vec::with_capacity(capacity);
@@ -200,12 +494,19 @@ async fn traces_to_df(
     let mut block_number: Vec<u32> = Vec::with_capacity(capacity);
     let mut block_hash: Vec<Vec<u8>> = Vec::with_capacity(capacity);
     let mut error: Vec<Option<String>> = Vec::with_capacity(capacity);
+    let mut function_selector: Vec<Option<Vec<u8>>> = Vec::with_capacity(capacity);
+    let mut function_name: Vec<Option<String>> = Vec::with_capacity(capacity);
+    let mut function_inputs: Vec<Option<String>> = Vec::with_capacity(capacity);
+    let mut function_outputs: Vec<Option<String>> = Vec::with_capacity(capacity);
 
     let mut n_rows = 0;
     while let Some(message) = rx.recv().await {
         match message {
             Ok(traces) => {
                 for trace in traces.iter() {
+                    if !trace_matches(trace, from_address, to_address) {
+                        continue
+                    }
                     if let (Some(tx_hash), Some(tx_pos)) =
                         (trace.transaction_hash, trace.transaction_position)
                     {
@@ -426,6 +727,38 @@ async fn traces_to_df(
                         if include_error {
                             error.push(trace.error.clone());
                         }
+
+                        // decode call data of calls to known functions
+                        let input = match &trace.action {
+                            Action::Call(action) => Some(&action.input),
+                            _ => None,
+                        };
+                        let decoded = input.and_then(|input| {
+                            conversions::decode_function_input(&functions, input)
+                        });
+                        if include_function_selector {
+                            function_selector.push(
+                                input
+                                    .and_then(|input| conversions::call_selector(input))
+                                    .map(|x| x.to_vec()),
+                            );
+                        }
+                        if include_function_name {
+                            function_name
+                                .push(decoded.as_ref().map(|(function, _)| function.name.clone()));
+                        }
+                        if include_function_outputs {
+                            let output = match (&decoded, &trace.result) {
+                                (Some((function, _)), Some(Res::Call(result))) => {
+                                    conversions::decode_function_output(function, &result.output)
+                                }
+                                _ => None,
+                            };
+                            function_outputs.push(output);
+                        }
+                        if include_function_inputs {
+                            function_inputs.push(decoded.map(|(_, inputs)| inputs));
+                        }
                     }
                 }
             }
@@ -455,6 +788,10 @@ async fn traces_to_df(
     with_series!(cols, "block_number", block_number, schema);
     with_series_binary!(cols, "block_hash", block_hash, schema);
     with_series!(cols, "error", error, schema);
+    with_series_binary!(cols, "function_selector", function_selector, schema);
+    with_series!(cols, "function_name", function_name, schema);
+    with_series!(cols, "function_inputs", function_inputs, schema);
+    with_series!(cols, "function_outputs", function_outputs, schema);
 
     if schema.has_column("chain_id") {
         cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
@@ -462,3 +799,93 @@ async fn traces_to_df(
 
     DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(typ: &str, from: u64, to: u64, calls: Vec<CallFrame>) -> CallFrame {
+        CallFrame {
+            typ: typ.to_string(),
+            from: H160::from_low_u64_be(from),
+            to: Some(NameOrAddress::Address(H160::from_low_u64_be(to))),
+            value: Some(U256::from(7)),
+            calls: if calls.is_empty() { None } else { Some(calls) },
+            ..Default::default()
+        }
+    }
+
+    fn flatten(root: CallFrame) -> Vec<Trace> {
+        let tx_trace = GethTrace::Known(GethTraceFrame::CallTracer(root));
+        geth_call_traces(tx_trace, 1, H256::zero(), H256::zero(), 3).unwrap()
+    }
+
+    #[test]
+    fn geth_call_traces_are_flattened_depth_first() {
+        let root = frame(
+            "CALL",
+            1,
+            2,
+            vec![
+                frame("DELEGATECALL", 2, 3, vec![frame("STATICCALL", 2, 4, vec![])]),
+                frame("CALL", 2, 5, vec![]),
+            ],
+        );
+        let traces = flatten(root);
+        let addresses: Vec<Vec<usize>> =
+            traces.iter().map(|trace| trace.trace_address.clone()).collect();
+        assert_eq!(addresses, vec![vec![], vec![0], vec![0, 0], vec![1]]);
+        let subtraces: Vec<usize> = traces.iter().map(|trace| trace.subtraces).collect();
+        assert_eq!(subtraces, vec![2, 1, 0, 0]);
+        assert!(traces.iter().all(|trace| trace.transaction_position == Some(3)));
+        match &traces[2].action {
+            Action::Call(action) => {
+                assert_eq!(action.call_type, CallType::StaticCall);
+                assert_eq!(action.to, H160::from_low_u64_be(4));
+            }
+            action => panic!("expected call, got {:?}", action),
+        }
+    }
+
+    #[test]
+    fn geth_call_frames_map_to_parity_actions() {
+        let mut failed = frame("CALL", 2, 6, vec![]);
+        failed.error = Some("execution reverted".to_string());
+        let root = frame(
+            "CALL",
+            1,
+            2,
+            vec![
+                frame("CREATE2", 2, 3, vec![]),
+                frame("SELFDESTRUCT", 2, 4, vec![]),
+                failed,
+                frame("UNKNOWN", 2, 5, vec![]),
+            ],
+        );
+        let traces = flatten(root);
+
+        assert_eq!(traces[1].action_type, ActionType::Create);
+        match &traces[1].result {
+            Some(Res::Create(result)) => assert_eq!(result.address, H160::from_low_u64_be(3)),
+            result => panic!("expected create result, got {:?}", result),
+        }
+
+        assert_eq!(traces[2].action_type, ActionType::Suicide);
+        match &traces[2].action {
+            Action::Suicide(action) => {
+                assert_eq!(action.address, H160::from_low_u64_be(2));
+                assert_eq!(action.refund_address, H160::from_low_u64_be(4));
+                assert_eq!(action.balance, U256::from(7));
+            }
+            action => panic!("expected suicide, got {:?}", action),
+        }
+
+        assert_eq!(traces[3].result, None);
+        assert_eq!(traces[3].error, Some("execution reverted".to_string()));
+
+        match &traces[4].action {
+            Action::Call(action) => assert_eq!(action.call_type, CallType::None),
+            action => panic!("expected call, got {:?}", action),
+        }
+    }
+}
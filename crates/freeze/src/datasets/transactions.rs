@@ -1,10 +1,13 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, future::Future, sync::Arc};
 
+use ethers::prelude::*;
 use polars::prelude::*;
+use tokio::{sync::mpsc, task};
 
 use super::{blocks, blocks_and_transactions};
 use crate::types::{
-    BlockChunk, CollectError, ColumnType, Dataset, Datatype, RowFilter, Source, Table, Transactions,
+    BlockChunk, CollectError, ColumnType, Dataset, Datatype, RowFilter, Source, Table,
+    TransactionChunk, Transactions,
 };
 
 #[async_trait::async_trait]
@@ -35,6 +38,11 @@ impl Dataset for Transactions {
             ("transaction_type", ColumnType::UInt32),
             ("max_priority_fee_per_gas", ColumnType::UInt64),
             ("max_fee_per_gas", ColumnType::UInt64),
+            ("max_fee_per_blob_gas", ColumnType::UInt64),
+            ("blob_versioned_hashes", ColumnType::BinaryList),
+            ("function_selector", ColumnType::Binary),
+            ("function_name", ColumnType::String),
+            ("function_inputs", ColumnType::String),
             ("chain_id", ColumnType::UInt64),
         ])
     }
@@ -67,17 +75,153 @@ impl Dataset for Transactions {
         chunk: &BlockChunk,
         source: &Source,
         schema: &Table,
-        _filter: Option<&RowFilter>,
+        filter: Option<&RowFilter>,
     ) -> Result<DataFrame, CollectError> {
         let include_gas_used = schema.has_column("gas_used");
         let rx =
             blocks_and_transactions::fetch_blocks_and_transactions(chunk, source, include_gas_used)
                 .await;
-        let output = blocks::blocks_to_dfs(rx, &None, &Some(schema), source.chain_id).await;
+        let output = blocks::blocks_to_dfs(rx, &None, &Some(schema), filter, source.chain_id).await;
         match output {
             Ok((_, Some(txs_df))) => Ok(txs_df),
             Ok((_, _)) => Err(CollectError::BadSchemaError),
             Err(e) => Err(e),
         }
     }
+
+    async fn collect_transaction_chunk(
+        &self,
+        chunk: &TransactionChunk,
+        source: &Source,
+        schema: &Table,
+        filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let hashes = transaction_hashes(chunk)?;
+        let include_gas_used = schema.has_column("gas_used");
+        let rx = fetch_transactions(&hashes, source, include_gas_used).await;
+        transactions_to_df(rx, schema, filter, source.chain_id).await
+    }
+}
+
+/// hashes of the transactions in a transaction chunk
+pub(crate) fn transaction_hashes(chunk: &TransactionChunk) -> Result<Vec<H256>, CollectError> {
+    match chunk {
+        TransactionChunk::Values(values) => values
+            .iter()
+            .map(|value| match value.len() {
+                32 => Ok(H256::from_slice(value)),
+                _ => Err(CollectError::CollectError("invalid transaction hash".to_string())),
+            })
+            .collect(),
+        TransactionChunk::Range(_, _) => Err(CollectError::CollectError(
+            "transaction chunks must list transaction hashes".to_string(),
+        )),
+    }
+}
+
+/// fetch one message per transaction hash, each fetch runs in its own task
+///
+/// a concurrency permit and rate limit are taken before calling `fetch`, any further requests
+/// made by `fetch` should wait on the rate limiter of the source themselves
+pub(crate) async fn fetch_by_hash<T, F, Fut>(
+    hashes: &[H256],
+    source: &Source,
+    fetch: F,
+) -> mpsc::Receiver<T>
+where
+    T: Send + 'static,
+    F: Fn(Arc<Source>, H256) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = T> + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(hashes.len().max(1));
+    let source = Arc::new(source.clone());
+    let fetch = Arc::new(fetch);
+
+    for hash in hashes.iter().copied() {
+        let tx = tx.clone();
+        let source = Arc::clone(&source);
+        let fetch = Arc::clone(&fetch);
+        task::spawn(async move {
+            let permit = match source.semaphore.clone() {
+                Some(semaphore) => Some(semaphore.acquire_owned().await),
+                _ => None,
+            };
+            if let Some(limiter) = source.rate_limiter.as_ref() {
+                Arc::clone(limiter).until_ready().await;
+            }
+            let message = fetch(Arc::clone(&source), hash).await;
+            drop(permit);
+            match tx.send(message).await {
+                Ok(_) => {}
+                Err(tokio::sync::mpsc::error::SendError(_e)) => {
+                    eprintln!("send error, try using a rate limit with --requests-per-second or limiting max concurrency with --max-concurrent-requests");
+                    std::process::exit(1)
+                }
+            }
+        });
+    }
+    rx
+}
+
+/// get a transaction by hash, along with its block number and index within the block
+pub(crate) async fn get_mined_transaction(
+    provider: &Provider<Http>,
+    hash: H256,
+) -> Result<(Transaction, u64, u64), CollectError> {
+    let tx = match provider.get_transaction(hash).await {
+        Ok(Some(tx)) => tx,
+        Ok(None) => return Err(CollectError::CollectError("transaction not in node".to_string())),
+        Err(e) => return Err(CollectError::ProviderError(e)),
+    };
+    match (tx.block_number, tx.transaction_index) {
+        (Some(number), Some(index)) => Ok((tx, number.as_u64(), index.as_u64())),
+        _ => Err(CollectError::CollectError("transaction is not mined yet".to_string())),
+    }
+}
+
+async fn fetch_transactions(
+    hashes: &[H256],
+    source: &Source,
+    include_gas_used: bool,
+) -> mpsc::Receiver<Result<(Transaction, Option<u32>), CollectError>> {
+    fetch_by_hash(hashes, source, move |source, hash| async move {
+        let transaction = match get_mined_transaction(&source.provider, hash).await {
+            Ok((transaction, _, _)) => transaction,
+            Err(e) => return Err(e),
+        };
+        if !include_gas_used {
+            return Ok((transaction, None))
+        }
+        if let Some(limiter) = source.rate_limiter.as_ref() {
+            Arc::clone(limiter).until_ready().await;
+        }
+        match source.provider.get_transaction_receipt(hash).await {
+            Ok(Some(TransactionReceipt { gas_used: Some(gas_used), .. })) => {
+                Ok((transaction, Some(gas_used.as_u32())))
+            }
+            Ok(_) => {
+                Err(CollectError::CollectError("gas_used not available from node".to_string()))
+            }
+            Err(e) => Err(CollectError::ProviderError(e)),
+        }
+    })
+    .await
+}
+
+async fn transactions_to_df(
+    mut rx: mpsc::Receiver<Result<(Transaction, Option<u32>), CollectError>>,
+    schema: &Table,
+    filter: Option<&RowFilter>,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let filter = blocks::TransactionFilter::new(filter);
+    let mut columns = blocks::TransactionColumns::new(100);
+    let mut n_txs = 0;
+    while let Some(message) = rx.recv().await {
+        let (tx, gas_used) = message?;
+        if blocks::process_transaction(&tx, schema, &mut columns, gas_used, &filter) {
+            n_txs += 1;
+        }
+    }
+    columns.create_df(schema, chain_id, n_txs)
 }
@@ -0,0 +1,179 @@
+use std::{collections::HashMap, sync::Arc};
+
+use ethers::prelude::*;
+use polars::prelude::*;
+use tokio::{sync::mpsc, task};
+
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        conversions::ToVecHex, BlockChunk, CollectError, ColumnType, Dataset, Datatype, RowFilter,
+        Source, Table, Uncles,
+    },
+    with_series, with_series_binary,
+};
+
+#[async_trait::async_trait]
+impl Dataset for Uncles {
+    fn datatype(&self) -> Datatype {
+        Datatype::Uncles
+    }
+
+    fn name(&self) -> &'static str {
+        "uncles"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("uncle_index", ColumnType::UInt32),
+            ("hash", ColumnType::Binary),
+            ("number", ColumnType::UInt32),
+            ("parent_hash", ColumnType::Binary),
+            ("author", ColumnType::Binary),
+            ("state_root", ColumnType::Binary),
+            ("timestamp", ColumnType::UInt32),
+            ("gas_used", ColumnType::UInt32),
+            ("gas_limit", ColumnType::UInt32),
+            ("difficulty", ColumnType::String),
+            ("extra_data", ColumnType::Binary),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec!["block_number", "uncle_index", "hash", "number", "author", "timestamp", "gas_used"]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["block_number".to_string(), "uncle_index".to_string()]
+    }
+
+    async fn collect_block_chunk(
+        &self,
+        chunk: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        _filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let rx = fetch_uncles(chunk, source).await;
+        uncles_to_df(rx, schema, source.chain_id).await
+    }
+}
+
+async fn fetch_uncles(
+    block_chunk: &BlockChunk,
+    source: &Source,
+) -> mpsc::Receiver<Result<(u64, Vec<Block<H256>>), CollectError>> {
+    let (tx, rx) = mpsc::channel(block_chunk.numbers().len());
+    let source = Arc::new(source.clone());
+
+    for number in block_chunk.numbers() {
+        let tx = tx.clone();
+        let source = Arc::clone(&source);
+        task::spawn(async move {
+            let result = get_uncles(number, source).await.map(|uncles| (number, uncles));
+            match tx.send(result).await {
+                Ok(_) => {}
+                Err(tokio::sync::mpsc::error::SendError(_e)) => {
+                    eprintln!("send error, try using a rate limit with --requests-per-second or limiting max concurrency with --max-concurrent-requests");
+                    std::process::exit(1)
+                }
+            }
+        });
+    }
+    rx
+}
+
+/// get the uncle headers of a block, one eth_getUncleByBlockNumberAndIndex per uncle
+async fn get_uncles(number: u64, source: Arc<Source>) -> Result<Vec<Block<H256>>, CollectError> {
+    let _permit = match source.semaphore.clone() {
+        Some(semaphore) => Some(semaphore.acquire_owned().await),
+        _ => None,
+    };
+    if let Some(limiter) = source.rate_limiter.as_ref() {
+        Arc::clone(limiter).until_ready().await;
+    }
+    let n_uncles = match source.provider.get_block(number).await {
+        Ok(Some(block)) => block.uncles.len(),
+        Ok(None) => return Err(CollectError::CollectError("block not in node".to_string())),
+        Err(e) => return Err(CollectError::ProviderError(e)),
+    };
+
+    let mut uncles = Vec::with_capacity(n_uncles);
+    for index in 0..n_uncles {
+        if let Some(limiter) = source.rate_limiter.as_ref() {
+            Arc::clone(limiter).until_ready().await;
+        }
+        let block = BlockId::Number(BlockNumber::Number(number.into()));
+        match source.provider.get_uncle(block, U64::from(index)).await {
+            Ok(Some(uncle)) => uncles.push(uncle),
+            Ok(None) => return Err(CollectError::CollectError("uncle not in node".to_string())),
+            Err(e) => return Err(CollectError::ProviderError(e)),
+        }
+    }
+    Ok(uncles)
+}
+
+async fn uncles_to_df(
+    mut rx: mpsc::Receiver<Result<(u64, Vec<Block<H256>>), CollectError>>,
+    schema: &Table,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut uncle_index: Vec<u32> = Vec::new();
+    let mut hash: Vec<Option<Vec<u8>>> = Vec::new();
+    let mut number: Vec<Option<u32>> = Vec::new();
+    let mut parent_hash: Vec<Vec<u8>> = Vec::new();
+    let mut author: Vec<Option<Vec<u8>>> = Vec::new();
+    let mut state_root: Vec<Vec<u8>> = Vec::new();
+    let mut timestamp: Vec<u32> = Vec::new();
+    let mut gas_used: Vec<u32> = Vec::new();
+    let mut gas_limit: Vec<u32> = Vec::new();
+    let mut difficulty: Vec<String> = Vec::new();
+    let mut extra_data: Vec<Vec<u8>> = Vec::new();
+
+    let mut n_rows = 0;
+    while let Some(message) = rx.recv().await {
+        match message {
+            Ok((including_block, uncles)) => {
+                for (index, uncle) in uncles.iter().enumerate() {
+                    n_rows += 1;
+                    block_number.push(including_block as u32);
+                    uncle_index.push(index as u32);
+                    hash.push(uncle.hash.map(|x| x.as_bytes().to_vec()));
+                    number.push(uncle.number.map(|x| x.as_u32()));
+                    parent_hash.push(uncle.parent_hash.as_bytes().to_vec());
+                    author.push(uncle.author.map(|x| x.as_bytes().to_vec()));
+                    state_root.push(uncle.state_root.as_bytes().to_vec());
+                    timestamp.push(uncle.timestamp.as_u32());
+                    gas_used.push(uncle.gas_used.as_u32());
+                    gas_limit.push(uncle.gas_limit.as_u32());
+                    difficulty.push(uncle.difficulty.to_string());
+                    extra_data.push(uncle.extra_data.to_vec());
+                }
+            }
+            Err(e) => return Err(CollectError::RPCError(e.to_string())),
+        }
+    }
+
+    let mut cols = Vec::new();
+    with_series!(cols, "block_number", block_number, schema);
+    with_series!(cols, "uncle_index", uncle_index, schema);
+    with_series_binary!(cols, "hash", hash, schema);
+    with_series!(cols, "number", number, schema);
+    with_series_binary!(cols, "parent_hash", parent_hash, schema);
+    with_series_binary!(cols, "author", author, schema);
+    with_series_binary!(cols, "state_root", state_root, schema);
+    with_series!(cols, "timestamp", timestamp, schema);
+    with_series!(cols, "gas_used", gas_used, schema);
+    with_series!(cols, "gas_limit", gas_limit, schema);
+    with_series!(cols, "difficulty", difficulty, schema);
+    with_series_binary!(cols, "extra_data", extra_data, schema);
+
+    if schema.has_column("chain_id") {
+        cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+    }
+
+    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+}
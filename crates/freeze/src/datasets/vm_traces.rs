@@ -6,10 +6,13 @@ use tokio::sync::mpsc;
 
 use crate::{
     dataframes::SortableDataFrame,
-    datasets::state_diffs,
+    datasets::{
+        state_diffs::{self, BlockTraces},
+        traces, transactions,
+    },
     types::{
         conversions::ToVecHex, BlockChunk, CollectError, ColumnType, Dataset, Datatype, RowFilter,
-        Source, Table, ToVecU8, VmTraces,
+        Source, Table, ToVecU8, TraceBackend, TransactionChunk, VmTraces,
     },
     with_series, with_series_binary,
 };
@@ -37,6 +40,7 @@ impl Dataset for VmTraces {
             ("storage_key", ColumnType::Binary),
             ("storage_val", ColumnType::Binary),
             ("op", ColumnType::String),
+            ("depth", ColumnType::UInt32),
             ("chain_id", ColumnType::Int64),
         ])
     }
@@ -54,20 +58,145 @@ impl Dataset for VmTraces {
         chunk: &BlockChunk,
         source: &Source,
         schema: &Table,
-        _filter: Option<&RowFilter>,
+        filter: Option<&RowFilter>,
     ) -> Result<DataFrame, CollectError> {
-        let rx = fetch_vm_traces(chunk, source).await;
-        vm_traces_to_df(rx, schema, source.chain_id).await
+        let opcodes = filter.and_then(|f| f.opcodes.as_deref());
+        match source.trace_backend {
+            TraceBackend::Parity => {
+                let rx = fetch_vm_traces(chunk, source).await;
+                vm_traces_to_df(rx, schema, opcodes, source.chain_id).await
+            }
+            TraceBackend::Geth => {
+                let rx = fetch_struct_logs(chunk, source, schema).await;
+                struct_logs_to_df(rx, schema, opcodes, source.chain_id).await
+            }
+        }
+    }
+
+    async fn collect_transaction_chunk(
+        &self,
+        chunk: &TransactionChunk,
+        source: &Source,
+        schema: &Table,
+        filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let hashes = transactions::transaction_hashes(chunk)?;
+        let opcodes = filter.and_then(|f| f.opcodes.as_deref());
+        match source.trace_backend {
+            TraceBackend::Parity => {
+                let rx = state_diffs::fetch_transaction_block_traces(
+                    &hashes,
+                    &[TraceType::VmTrace],
+                    source,
+                )
+                .await;
+                vm_traces_to_df(rx, schema, opcodes, source.chain_id).await
+            }
+            TraceBackend::Geth => {
+                let rx = fetch_transaction_struct_logs(&hashes, source, schema).await;
+                struct_logs_to_df(rx, schema, opcodes, source.chain_id).await
+            }
+        }
     }
 }
 
 async fn fetch_vm_traces(
     block_chunk: &BlockChunk,
     source: &Source,
-) -> mpsc::Receiver<(u32, Result<Vec<BlockTrace>, CollectError>)> {
+) -> mpsc::Receiver<BlockTraces<BlockTrace>> {
     state_diffs::fetch_block_traces(block_chunk, &[TraceType::VmTrace], source).await
 }
 
+/// fetch opcode traces using geth's default struct logger
+async fn fetch_struct_logs(
+    block_chunk: &BlockChunk,
+    source: &Source,
+    schema: &Table,
+) -> mpsc::Receiver<BlockTraces<DefaultFrame>> {
+    let (tx, rx) = mpsc::channel(block_chunk.size() as usize);
+    let options = struct_log_options(schema);
+    for number in block_chunk.numbers() {
+        let tx = tx.clone();
+        let provider = source.provider.clone();
+        let semaphore = source.semaphore.clone();
+        let rate_limiter = source.rate_limiter.as_ref().map(Arc::clone);
+        let options = options.clone();
+        tokio::spawn(async move {
+            let _permit = match semaphore {
+                Some(semaphore) => Some(Arc::clone(&semaphore).acquire_owned().await),
+                _ => None,
+            };
+            if let Some(limiter) = &rate_limiter {
+                Arc::clone(limiter).until_ready().await;
+            }
+            let result =
+                traces::geth_debug_trace_block(&provider, rate_limiter, number, options).await;
+            let result = result.and_then(|(_block, tx_traces)| {
+                tx_traces
+                    .into_iter()
+                    .map(|tx_trace| match tx_trace {
+                        GethTrace::Known(GethTraceFrame::Default(frame)) => Ok(frame),
+                        _ => Err(CollectError::CollectError(
+                            "invalid struct logger result".to_string(),
+                        )),
+                    })
+                    .collect()
+            });
+            match tx.send((number as u32, 0, result)).await {
+                Ok(_) => {}
+                Err(tokio::sync::mpsc::error::SendError(_e)) => {
+                    eprintln!("send error, try using a rate limit with --requests-per-second or limiting max concurrency with --max-concurrent-requests");
+                    std::process::exit(1)
+                }
+            }
+        });
+    }
+
+    rx
+}
+
+/// fetch opcode traces of individual transactions using geth's default struct logger
+async fn fetch_transaction_struct_logs(
+    hashes: &[H256],
+    source: &Source,
+    schema: &Table,
+) -> mpsc::Receiver<BlockTraces<DefaultFrame>> {
+    let options = struct_log_options(schema);
+    transactions::fetch_by_hash(hashes, source, move |source, hash| {
+        let options = options.clone();
+        async move {
+            let (_, number, index) =
+                match transactions::get_mined_transaction(&source.provider, hash).await {
+                    Ok(mined) => mined,
+                    Err(e) => return (0, 0, Err(e)),
+                };
+            if let Some(limiter) = source.rate_limiter.as_ref() {
+                Arc::clone(limiter).until_ready().await;
+            }
+            let result = match source.provider.debug_trace_transaction(hash, options).await {
+                Ok(GethTrace::Known(GethTraceFrame::Default(frame))) => Ok(vec![frame]),
+                Ok(_) => {
+                    Err(CollectError::CollectError("invalid struct logger result".to_string()))
+                }
+                Err(e) => Err(CollectError::ProviderError(e)),
+            };
+            (number as u32, index as u32, result)
+        }
+    })
+    .await
+}
+
+/// struct logger options, memory is only captured when memory columns are requested
+fn struct_log_options(schema: &Table) -> GethDebugTracingOptions {
+    GethDebugTracingOptions {
+        disable_storage: Some(true),
+        disable_stack: Some(false),
+        enable_memory: Some(schema.has_column("mem_off") || schema.has_column("mem_data")),
+        enable_return_data: Some(false),
+        ..Default::default()
+    }
+}
+
 struct VmTraceColumns {
     block_number: Vec<u32>,
     transaction_position: Vec<u32>,
@@ -80,36 +209,68 @@ struct VmTraceColumns {
     storage_key: Vec<Option<Vec<u8>>>,
     storage_val: Vec<Option<Vec<u8>>>,
     op: Vec<String>,
+    depth: Vec<u32>,
     n_rows: usize,
 }
 
+impl VmTraceColumns {
+    fn new(capacity: usize) -> Self {
+        Self {
+            block_number: Vec::with_capacity(capacity),
+            transaction_position: Vec::with_capacity(capacity),
+            pc: Vec::with_capacity(capacity),
+            cost: Vec::with_capacity(capacity),
+            used: Vec::with_capacity(capacity),
+            push: Vec::with_capacity(capacity),
+            mem_off: Vec::with_capacity(capacity),
+            mem_data: Vec::with_capacity(capacity),
+            storage_key: Vec::with_capacity(capacity),
+            storage_val: Vec::with_capacity(capacity),
+            op: Vec::with_capacity(capacity),
+            depth: Vec::with_capacity(capacity),
+            n_rows: 0,
+        }
+    }
+
+    fn create_df(self, schema: &Table, chain_id: u64) -> Result<DataFrame, CollectError> {
+        let mut cols = Vec::new();
+
+        with_series!(cols, "block_number", self.block_number, schema);
+        with_series!(cols, "transaction_position", self.transaction_position, schema);
+        with_series!(cols, "pc", self.pc, schema);
+        with_series!(cols, "cost", self.cost, schema);
+        with_series!(cols, "used", self.used, schema);
+        with_series_binary!(cols, "push", self.push, schema);
+        with_series!(cols, "mem_off", self.mem_off, schema);
+        with_series_binary!(cols, "mem_data", self.mem_data, schema);
+        with_series_binary!(cols, "storage_key", self.storage_key, schema);
+        with_series_binary!(cols, "storage_val", self.storage_val, schema);
+        with_series!(cols, "op", self.op, schema);
+        with_series!(cols, "depth", self.depth, schema);
+
+        if schema.has_column("chain_id") {
+            cols.push(Series::new("chain_id", vec![chain_id; self.n_rows]));
+        };
+
+        DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+    }
+}
+
 async fn vm_traces_to_df(
-    mut rx: mpsc::Receiver<(u32, Result<Vec<BlockTrace>, CollectError>)>,
+    mut rx: mpsc::Receiver<BlockTraces<BlockTrace>>,
     schema: &Table,
+    opcodes: Option<&[String]>,
     chain_id: u64,
 ) -> Result<DataFrame, CollectError> {
-    let capacity = 100;
-    let mut columns = VmTraceColumns {
-        block_number: Vec::with_capacity(capacity),
-        transaction_position: Vec::with_capacity(capacity),
-        pc: Vec::with_capacity(capacity),
-        cost: Vec::with_capacity(capacity),
-        used: Vec::with_capacity(capacity),
-        push: Vec::with_capacity(capacity),
-        mem_off: Vec::with_capacity(capacity),
-        mem_data: Vec::with_capacity(capacity),
-        storage_key: Vec::with_capacity(capacity),
-        storage_val: Vec::with_capacity(capacity),
-        op: Vec::with_capacity(capacity),
-        n_rows: 0,
-    };
+    let mut columns = VmTraceColumns::new(100);
 
     while let Some(message) = rx.recv().await {
         match message {
-            (number, Ok(block_traces)) => {
+            (number, first_index, Ok(block_traces)) => {
                 for (tx_pos, block_trace) in block_traces.into_iter().enumerate() {
+                    let tx_pos = first_index + tx_pos as u32;
                     if let Some(vm_trace) = block_trace.vm_trace {
-                        add_ops(vm_trace, schema, &mut columns, number, tx_pos as u32)
+                        add_ops(vm_trace, schema, &mut columns, number, tx_pos, 1, opcodes)
                     }
                 }
             }
@@ -117,25 +278,129 @@ async fn vm_traces_to_df(
         }
     }
 
-    let mut cols = Vec::new();
-
-    with_series!(cols, "block_number", columns.block_number, schema);
-    with_series!(cols, "transaction_position", columns.transaction_position, schema);
-    with_series!(cols, "pc", columns.pc, schema);
-    with_series!(cols, "cost", columns.cost, schema);
-    with_series!(cols, "used", columns.used, schema);
-    with_series_binary!(cols, "push", columns.push, schema);
-    with_series!(cols, "mem_off", columns.mem_off, schema);
-    with_series_binary!(cols, "mem_data", columns.mem_data, schema);
-    with_series_binary!(cols, "storage_key", columns.storage_key, schema);
-    with_series_binary!(cols, "storage_val", columns.storage_val, schema);
-    with_series!(cols, "op", columns.op, schema);
-
-    if schema.has_column("chain_id") {
-        cols.push(Series::new("chain_id", vec![chain_id; columns.n_rows]));
-    };
+    columns.create_df(schema, chain_id)
+}
+
+async fn struct_logs_to_df(
+    mut rx: mpsc::Receiver<BlockTraces<DefaultFrame>>,
+    schema: &Table,
+    opcodes: Option<&[String]>,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut columns = VmTraceColumns::new(100);
 
-    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+    while let Some(message) = rx.recv().await {
+        match message {
+            (number, first_index, Ok(frames)) => {
+                for (tx_pos, frame) in frames.into_iter().enumerate() {
+                    let tx_pos = first_index + tx_pos as u32;
+                    add_struct_logs(
+                        frame.struct_logs,
+                        schema,
+                        &mut columns,
+                        number,
+                        tx_pos,
+                        opcodes,
+                    )
+                }
+            }
+            (_, _, Err(e)) => return Err(e),
+        }
+    }
+
+    columns.create_df(schema, chain_id)
+}
+
+/// add geth struct logs using the same semantics as parity vm traces
+///
+/// push holds the stack top after the instruction, memory columns hold the range written by
+/// memory writing ops (MSTORE, MSTORE8, *COPY), and storage columns are filled for SSTORE
+fn add_struct_logs(
+    struct_logs: Vec<StructLog>,
+    schema: &Table,
+    columns: &mut VmTraceColumns,
+    number: u32,
+    tx_pos: u32,
+    opcodes: Option<&[String]>,
+) {
+    for (index, log) in struct_logs.iter().enumerate() {
+        if matches!(opcodes, Some(opcodes) if !opcodes.contains(&log.op)) {
+            continue
+        }
+        columns.n_rows += 1;
+
+        if schema.has_column("block_number") {
+            columns.block_number.push(number);
+        };
+        if schema.has_column("transaction_position") {
+            columns.transaction_position.push(tx_pos);
+        };
+        if schema.has_column("pc") {
+            columns.pc.push(log.pc);
+        };
+        if schema.has_column("cost") {
+            columns.cost.push(log.gas_cost);
+        };
+        if schema.has_column("used") {
+            columns.used.push(Some(log.gas.saturating_sub(log.gas_cost)));
+        };
+        if schema.has_column("push") {
+            let next = struct_logs.get(index + 1).filter(|next| next.depth == log.depth);
+            let top = next.and_then(|next| next.stack.as_ref()).and_then(|stack| stack.last());
+            columns.push.push(top.map(|value| value.to_vec_u8()));
+        };
+        let written = written_memory(&struct_logs, index);
+        if schema.has_column("mem_off") {
+            columns.mem_off.push(written.as_ref().map(|(offset, _)| *offset as u32));
+        };
+        if schema.has_column("mem_data") {
+            columns.mem_data.push(written.map(|(_, data)| data));
+        };
+        let stored = match (log.op.as_str(), &log.stack) {
+            ("SSTORE", Some(stack)) if stack.len() >= 2 => {
+                Some((stack[stack.len() - 1], stack[stack.len() - 2]))
+            }
+            _ => None,
+        };
+        if schema.has_column("storage_key") {
+            columns.storage_key.push(stored.map(|(key, _)| key.to_vec_u8()));
+        };
+        if schema.has_column("storage_val") {
+            columns.storage_val.push(stored.map(|(_, val)| val.to_vec_u8()));
+        };
+        if schema.has_column("op") {
+            columns.op.push(log.op.clone());
+        };
+        if schema.has_column("depth") {
+            columns.depth.push(log.depth as u32);
+        };
+    }
+}
+
+/// memory range written by a struct log, read from the memory of the following struct log
+fn written_memory(struct_logs: &[StructLog], index: usize) -> Option<(usize, Vec<u8>)> {
+    let log = struct_logs.get(index)?;
+    let stack = log.stack.as_ref()?;
+    let arg = |position: usize| {
+        let value = stack.get(stack.len().checked_sub(position + 1)?)?;
+        (*value <= U256::from(u32::MAX)).then(|| value.as_usize())
+    };
+    let (offset, size) = match log.op.as_str() {
+        "MSTORE" => (arg(0)?, 32),
+        "MSTORE8" => (arg(0)?, 1),
+        "CALLDATACOPY" | "CODECOPY" | "RETURNDATACOPY" | "MCOPY" => (arg(0)?, arg(2)?),
+        "EXTCODECOPY" => (arg(1)?, arg(3)?),
+        _ => return None,
+    };
+    let next = struct_logs.get(index + 1).filter(|next| next.depth == log.depth)?;
+    let memory: Vec<u8> = next
+        .memory
+        .as_ref()?
+        .iter()
+        .flat_map(|word| word.parse::<Bytes>().ok())
+        .flatten()
+        .collect();
+    memory.get(offset..offset + size).map(|data| (offset, data.to_vec()))
 }
 
 fn add_ops(
@@ -144,8 +409,21 @@ fn add_ops(
     columns: &mut VmTraceColumns,
     number: u32,
     tx_pos: u32,
+    depth: u32,
+    opcodes: Option<&[String]>,
 ) {
     for opcode in vm_trace.ops {
+        let op = match opcode.op {
+            ExecutedInstruction::Known(op) => op.to_string(),
+            ExecutedInstruction::Unknown(op) => op,
+        };
+        // skipped instructions can still have calls with matching instructions
+        if matches!(opcodes, Some(opcodes) if !opcodes.contains(&op)) {
+            if let Some(sub) = opcode.sub {
+                add_ops(sub, schema, columns, number, tx_pos, depth + 1, opcodes)
+            }
+            continue
+        }
         columns.n_rows += 1;
 
         if schema.has_column("block_number") {
@@ -219,14 +497,15 @@ fn add_ops(
             };
         }
         if schema.has_column("op") {
-            match opcode.op {
-                ExecutedInstruction::Known(op) => columns.op.push(op.to_string()),
-                ExecutedInstruction::Unknown(op) => columns.op.push(op),
-            }
+            columns.op.push(op);
+        };
+
+        if schema.has_column("depth") {
+            columns.depth.push(depth);
         };
 
         if let Some(sub) = opcode.sub {
-            add_ops(sub, schema, columns, number, tx_pos)
+            add_ops(sub, schema, columns, number, tx_pos, depth + 1, opcodes)
         }
     }
 }
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use ethers::prelude::*;
+use polars::prelude::*;
+use tokio::sync::mpsc;
+
+use super::blocks::{self, BlockTxGasTuple};
+use crate::{
+    dataframes::SortableDataFrame,
+    types::{
+        conversions::ToVecHex, BlockChunk, CollectError, ColumnType, Dataset, Datatype, RowFilter,
+        Source, Table, Withdrawals,
+    },
+    with_series, with_series_binary,
+};
+
+#[async_trait::async_trait]
+impl Dataset for Withdrawals {
+    fn datatype(&self) -> Datatype {
+        Datatype::Withdrawals
+    }
+
+    fn name(&self) -> &'static str {
+        "withdrawals"
+    }
+
+    fn column_types(&self) -> HashMap<&'static str, ColumnType> {
+        HashMap::from_iter(vec![
+            ("block_number", ColumnType::UInt32),
+            ("block_hash", ColumnType::Binary),
+            ("withdrawal_index", ColumnType::UInt64),
+            ("validator_index", ColumnType::UInt64),
+            ("address", ColumnType::Binary),
+            ("amount_gwei", ColumnType::UInt64),
+            ("chain_id", ColumnType::UInt64),
+        ])
+    }
+
+    fn default_columns(&self) -> Vec<&'static str> {
+        vec!["block_number", "withdrawal_index", "validator_index", "address", "amount_gwei"]
+    }
+
+    fn default_sort(&self) -> Vec<String> {
+        vec!["block_number".to_string(), "withdrawal_index".to_string()]
+    }
+
+    async fn collect_block_chunk(
+        &self,
+        chunk: &BlockChunk,
+        source: &Source,
+        schema: &Table,
+        _filter: Option<&RowFilter>,
+    ) -> Result<DataFrame, CollectError> {
+        let rx = blocks::fetch_blocks(chunk, source).await;
+        withdrawals_to_df(rx, schema, source.chain_id).await
+    }
+}
+
+async fn withdrawals_to_df(
+    mut rx: mpsc::Receiver<BlockTxGasTuple<TxHash>>,
+    schema: &Table,
+    chain_id: u64,
+) -> Result<DataFrame, CollectError> {
+    let mut block_number: Vec<u32> = Vec::new();
+    let mut block_hash: Vec<Vec<u8>> = Vec::new();
+    let mut withdrawal_index: Vec<u64> = Vec::new();
+    let mut validator_index: Vec<u64> = Vec::new();
+    let mut address: Vec<Vec<u8>> = Vec::new();
+    let mut amount_gwei: Vec<u64> = Vec::new();
+
+    let mut n_rows = 0;
+    while let Some(message) = rx.recv().await {
+        match message {
+            Ok((block, _)) => {
+                // blocks before shanghai have no withdrawals
+                if let (Some(number), Some(hash), Some(withdrawals)) =
+                    (block.number, block.hash, block.withdrawals)
+                {
+                    for withdrawal in withdrawals.iter() {
+                        n_rows += 1;
+                        block_number.push(number.as_u32());
+                        block_hash.push(hash.as_bytes().to_vec());
+                        withdrawal_index.push(withdrawal.index.as_u64());
+                        validator_index.push(withdrawal.validator_index.as_u64());
+                        address.push(withdrawal.address.as_bytes().to_vec());
+                        amount_gwei.push(withdrawal.amount.as_u64());
+                    }
+                }
+            }
+            Err(e) => return Err(CollectError::RPCError(e.to_string())),
+        }
+    }
+
+    let mut cols = Vec::new();
+    with_series!(cols, "block_number", block_number, schema);
+    with_series_binary!(cols, "block_hash", block_hash, schema);
+    with_series!(cols, "withdrawal_index", withdrawal_index, schema);
+    with_series!(cols, "validator_index", validator_index, schema);
+    with_series_binary!(cols, "address", address, schema);
+    with_series!(cols, "amount_gwei", amount_gwei, schema);
+
+    if schema.has_column("chain_id") {
+        cols.push(Series::new("chain_id", vec![chain_id; n_rows]));
+    }
+
+    DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)
+}
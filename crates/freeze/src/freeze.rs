@@ -154,7 +154,7 @@ async fn freeze_multi_datatype_chunk(
     // collect data
     let collect_result = mdt
         .multi_dataset()
-        .collect_chunk(&chunk, &source, query.schemas.clone(), HashMap::new())
+        .collect_chunk(&chunk, &source, query.schemas.clone(), query.row_filters.clone())
         .await;
     let mut dfs = match collect_result {
         Err(_e) => {
@@ -10,21 +10,34 @@ pub enum BinaryChunk {
     Range(Vec<u8>, Vec<u8>),
 }
 
+impl BinaryChunk {
+    /// digest of the sorted values of chunk, identifies chunks whose values are not contiguous
+    pub fn digest(&self) -> String {
+        let mut values = match self {
+            BinaryChunk::Values(values) => values.clone(),
+            BinaryChunk::Range(start, end) => vec![start.clone(), end.clone()],
+        };
+        values.sort();
+        let hash = ethers::utils::keccak256(values.concat());
+        hash[..8].iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
 impl ChunkData for BinaryChunk {
     type Inner = Vec<u8>;
 
-    fn format_item(_value: Self::Inner) -> String {
-        todo!()
+    fn format_item(value: Self::Inner) -> String {
+        prefix_hex::encode(value)
     }
 
     fn size(&self) -> u64 {
-        match (self.min_value(), self.max_value()) {
-            (Some(min), Some(max)) => {
-                let min_int = ethers::types::U256::from_big_endian(&min);
-                let max_int = ethers::types::U256::from_big_endian(&max);
+        match self {
+            BinaryChunk::Values(values) => values.len() as u64,
+            BinaryChunk::Range(start, end) => {
+                let min_int = ethers::types::U256::from_big_endian(start);
+                let max_int = ethers::types::U256::from_big_endian(end);
                 (max_int - min_int).as_u64()
             }
-            _ => 0,
         }
     }
 
@@ -42,3 +55,28 @@ impl ChunkData for BinaryChunk {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashes(values: &[u8]) -> BinaryChunk {
+        BinaryChunk::Values(values.iter().map(|value| vec![*value; 32]).collect())
+    }
+
+    #[test]
+    fn digest_ignores_order_of_values() {
+        let digest = hashes(&[1, 2, 3]).digest();
+        assert_eq!(digest.len(), 16);
+        assert_eq!(hashes(&[3, 1, 2]).digest(), digest);
+        assert_eq!(hashes(&[2, 3, 1]).digest(), digest);
+    }
+
+    #[test]
+    fn digest_differs_between_values() {
+        let digest = hashes(&[1, 2, 3]).digest();
+        assert_ne!(hashes(&[1, 2]).digest(), digest);
+        assert_ne!(hashes(&[1, 2, 4]).digest(), digest);
+        assert_ne!(hashes(&[1, 2, 3, 3]).digest(), digest);
+    }
+}
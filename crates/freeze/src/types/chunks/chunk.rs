@@ -1,6 +1,10 @@
 use crate::types::{FileError, FileOutput};
 
-use super::{binary_chunk::BinaryChunk, chunk_ops::ChunkData, number_chunk::NumberChunk};
+use super::{
+    binary_chunk::BinaryChunk,
+    chunk_ops::{stub_to_filepath, ChunkData},
+    number_chunk::NumberChunk,
+};
 
 /// block chunk
 pub type BlockChunk = NumberChunk;
@@ -20,8 +24,8 @@ pub enum Chunk {
     /// transaction chunk
     Transaction(TransactionChunk),
 
-    /// address chunk chunk
-    Address(AddressChunk),
+    /// address chunk, collected at every block of the block chunk
+    Address(AddressChunk, BlockChunk),
 }
 
 /// Chunk methods
@@ -30,8 +34,13 @@ impl Chunk {
     pub fn filepath(&self, name: &str, file_output: &FileOutput) -> Result<String, FileError> {
         match self {
             Chunk::Block(chunk) => chunk.filepath(name, file_output),
-            Chunk::Transaction(chunk) => chunk.filepath(name, file_output),
-            Chunk::Address(chunk) => chunk.filepath(name, file_output),
+            Chunk::Transaction(chunk) => {
+                stub_to_filepath(format!("txs_{}", chunk.digest()), name, file_output)
+            }
+            Chunk::Address(chunk, blocks) => {
+                let stub = format!("{}__{}", chunk.stub()?, blocks.stub()?);
+                stub_to_filepath(stub, name, file_output)
+            }
         }
     }
 }
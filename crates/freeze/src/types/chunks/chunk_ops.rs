@@ -29,16 +29,25 @@ pub trait ChunkData: Sized {
 
     /// get filepath for chunk
     fn filepath(&self, name: &str, file_output: &FileOutput) -> Result<String, FileError> {
-        let network_name = file_output.prefix.clone();
-        let pieces: Vec<String> = match &file_output.suffix {
-            Some(suffix) => vec![network_name, name.to_string(), self.stub()?, suffix.clone()],
-            None => vec![network_name, name.to_string(), self.stub()?],
-        };
-        let filename = format!("{}.{}", pieces.join("__"), file_output.format.as_str());
-        match file_output.output_dir.as_str() {
-            "." => Ok(filename),
-            output_dir => Ok(output_dir.to_string() + "/" + filename.as_str()),
-        }
+        stub_to_filepath(self.stub()?, name, file_output)
+    }
+}
+
+/// get filepath of a chunk with the given stub
+pub(crate) fn stub_to_filepath(
+    stub: String,
+    name: &str,
+    file_output: &FileOutput,
+) -> Result<String, FileError> {
+    let network_name = file_output.prefix.clone();
+    let pieces: Vec<String> = match &file_output.suffix {
+        Some(suffix) => vec![network_name, name.to_string(), stub, suffix.clone()],
+        None => vec![network_name, name.to_string(), stub],
+    };
+    let filename = format!("{}.{}", pieces.join("__"), file_output.format.as_str());
+    match file_output.output_dir.as_str() {
+        "." => Ok(filename),
+        output_dir => Ok(output_dir.to_string() + "/" + filename.as_str()),
     }
 }
 
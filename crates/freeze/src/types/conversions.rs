@@ -1,7 +1,12 @@
 /// conversion operations
-use ethers::prelude::*;
+use std::collections::HashMap;
+
+use ethers::{abi::Function, prelude::*};
+use polars::prelude::{NamedFrom, Series};
 use prefix_hex;
 
+use crate::types::ColumnType;
+
 /// Converts data to Vec<u8>
 pub trait ToVecU8 {
     /// Convert to Vec<u8>
@@ -30,6 +35,13 @@ impl ToVecU8 for Vec<U256> {
     }
 }
 
+/// Converts U256 to its 32 byte big-endian representation
+pub(crate) fn u256_to_bytes(value: &U256) -> Vec<u8> {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    bytes.to_vec()
+}
+
 // pub trait ToVecHex {
 //     fn to_vec_hex(&self) -> Vec<String>;
 // }
@@ -64,3 +76,215 @@ impl ToVecHex for Vec<Option<Vec<u8>>> {
         self.iter().map(|opt| opt.as_ref().map(|v| prefix_hex::encode(v.clone()))).collect()
     }
 }
+
+/// Formats a decoded ABI token as a human readable string
+pub(crate) fn format_token(token: &ethers::abi::Token) -> String {
+    use ethers::abi::Token;
+    match token {
+        Token::Address(address) => format!("{:?}", address),
+        Token::FixedBytes(bytes) | Token::Bytes(bytes) => prefix_hex::encode(bytes.clone()),
+        Token::Int(value) => I256::from_raw(*value).to_string(),
+        Token::Uint(value) => value.to_string(),
+        Token::Bool(value) => value.to_string(),
+        Token::String(value) => value.clone(),
+        Token::FixedArray(tokens) | Token::Array(tokens) => {
+            format!("[{}]", tokens.iter().map(format_token).collect::<Vec<_>>().join(", "))
+        }
+        Token::Tuple(tokens) => {
+            format!("({})", tokens.iter().map(format_token).collect::<Vec<_>>().join(", "))
+        }
+    }
+}
+
+/// Formats a list of decoded ABI tokens, unwrapping single values
+pub(crate) fn format_tokens(tokens: &[ethers::abi::Token]) -> String {
+    match tokens {
+        [token] => format_token(token),
+        tokens => format!("({})", tokens.iter().map(format_token).collect::<Vec<_>>().join(", ")),
+    }
+}
+
+/// Converts decoded ABI tokens to a Series of the given column type, mismatched tokens are null
+pub(crate) fn tokens_to_series(
+    name: &str,
+    tokens: Vec<Option<ethers::abi::Token>>,
+    column_type: ColumnType,
+) -> Series {
+    use ethers::abi::Token;
+    match column_type {
+        ColumnType::UInt64 => {
+            let values: Vec<Option<u64>> = tokens
+                .iter()
+                .map(|token| match token {
+                    Some(Token::Uint(value)) if value.bits() <= 64 => Some(value.as_u64()),
+                    _ => None,
+                })
+                .collect();
+            Series::new(name, values)
+        }
+        ColumnType::Int64 => {
+            let values: Vec<Option<i64>> = tokens
+                .iter()
+                .map(|token| match token {
+                    Some(Token::Int(value)) => i64::try_from(I256::from_raw(*value)).ok(),
+                    _ => None,
+                })
+                .collect();
+            Series::new(name, values)
+        }
+        ColumnType::Boolean => {
+            let values: Vec<Option<bool>> = tokens
+                .iter()
+                .map(|token| match token {
+                    Some(Token::Bool(value)) => Some(*value),
+                    _ => None,
+                })
+                .collect();
+            Series::new(name, values)
+        }
+        ColumnType::Binary | ColumnType::Hex => {
+            let values: Vec<Option<Vec<u8>>> = tokens
+                .iter()
+                .map(|token| match token {
+                    Some(Token::Address(address)) => Some(address.as_bytes().to_vec()),
+                    Some(Token::Bytes(bytes)) | Some(Token::FixedBytes(bytes)) => {
+                        Some(bytes.clone())
+                    }
+                    _ => None,
+                })
+                .collect();
+            match column_type {
+                ColumnType::Hex => Series::new(name, values.to_vec_hex()),
+                _ => Series::new(name, values),
+            }
+        }
+        _ => {
+            let values: Vec<Option<String>> =
+                tokens.iter().map(|token| token.as_ref().map(format_token)).collect();
+            Series::new(name, values)
+        }
+    }
+}
+
+/// Formats decoded ABI tokens as a JSON object keyed by parameter name
+pub(crate) fn tokens_to_json(
+    params: &[ethers::abi::Param],
+    tokens: &[ethers::abi::Token],
+) -> String {
+    let fields: Vec<String> = params
+        .iter()
+        .enumerate()
+        .zip(tokens)
+        .map(|((index, param), token)| {
+            let name = match param.name.as_str() {
+                "" => format!("arg{}", index),
+                name => name.to_string(),
+            };
+            format!("{}:{}", json_string(&name), token_to_json(token))
+        })
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+/// Formats a decoded ABI token as JSON, integers are quoted to keep their precision
+fn token_to_json(token: &ethers::abi::Token) -> String {
+    use ethers::abi::Token;
+    match token {
+        Token::Bool(value) => value.to_string(),
+        Token::String(value) => json_string(value),
+        Token::FixedArray(tokens) | Token::Array(tokens) | Token::Tuple(tokens) => {
+            format!("[{}]", tokens.iter().map(token_to_json).collect::<Vec<_>>().join(","))
+        }
+        token => json_string(&format_token(token)),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Indexes functions by their 4 byte selector
+pub(crate) fn function_selectors(functions: &[Function]) -> HashMap<[u8; 4], Function> {
+    functions.iter().map(|function| (function.short_signature(), function.clone())).collect()
+}
+
+/// Extracts the 4 byte selector of call data, if present
+pub(crate) fn call_selector(input: &[u8]) -> Option<[u8; 4]> {
+    input.get(..4).and_then(|selector| selector.try_into().ok())
+}
+
+/// Decodes call data into the called function and its inputs as JSON
+pub(crate) fn decode_function_input<'a>(
+    functions: &'a HashMap<[u8; 4], Function>,
+    input: &[u8],
+) -> Option<(&'a Function, String)> {
+    let function = functions.get(&call_selector(input)?)?;
+    let tokens = function.decode_input(&input[4..]).ok()?;
+    Some((function, tokens_to_json(&function.inputs, &tokens)))
+}
+
+/// Decodes the return data of a function as JSON
+pub(crate) fn decode_function_output(function: &Function, output: &[u8]) -> Option<String> {
+    let tokens = function.decode_output(output).ok()?;
+    Some(tokens_to_json(&function.outputs, &tokens))
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::{HumanReadableParser, Token};
+
+    use super::*;
+
+    fn function(signature: &str) -> Function {
+        HumanReadableParser::parse_function(signature).unwrap()
+    }
+
+    #[test]
+    fn json_strings_are_escaped() {
+        let function = function("function f(string memo)");
+        let token = Token::String("a\"b\\c\nd".to_string());
+        let json = tokens_to_json(&function.inputs, &[token]);
+        assert_eq!(json, r#"{"memo":"a\"b\\c\u000ad"}"#);
+    }
+
+    #[test]
+    fn decoded_inputs_name_unnamed_params_and_quote_integers() {
+        let function = function("function transfer(address to, uint256) returns (bool)");
+        let functions = function_selectors(&[function.clone()]);
+        let to = H160::from_low_u64_be(1);
+        let amount = U256::from(2).pow(U256::from(200));
+        let input = function.encode_input(&[Token::Address(to), Token::Uint(amount)]).unwrap();
+
+        let (decoded, json) = decode_function_input(&functions, &input).unwrap();
+        assert_eq!(decoded.name, "transfer");
+        let expected = format!(r#"{{"to":"{:?}","arg1":"{}"}}"#, to, amount);
+        assert_eq!(json, expected);
+
+        assert!(decode_function_input(&functions, &input[..3]).is_none());
+        assert!(decode_function_input(&functions, &[0, 0, 0, 0]).is_none());
+    }
+
+    #[test]
+    fn decoded_outputs_are_keyed_by_position() {
+        let function = function("function f() returns (bool, int256 delta, uint8[])");
+        let tokens = [
+            Token::Bool(true),
+            Token::Int(I256::from(-5).into_raw()),
+            Token::Array(vec![Token::Uint(U256::from(1)), Token::Uint(U256::from(2))]),
+        ];
+        let output = ethers::abi::encode(&tokens);
+        let json = decode_function_output(&function, &output).unwrap();
+        assert_eq!(json, r#"{"arg0":true,"delta":"-5","arg2":["1","2"]}"#);
+    }
+}
@@ -21,3 +21,19 @@ macro_rules! with_series_binary {
         }
     };
 }
+
+/// convert a Vec of binary lists to a list Series, as hex if specified, and add to Vec<Series>
+#[macro_export]
+macro_rules! with_series_binary_list {
+    ($all_series:expr, $name:expr, $value:expr, $schema:expr) => {
+        if $schema.has_column($name) {
+            let values: Vec<Series> = if let Some(ColumnType::HexList) = $schema.column_type($name)
+            {
+                $value.iter().map(|v| Series::new("", v.to_vec_hex())).collect()
+            } else {
+                $value.iter().map(|v| Series::new("", v.clone())).collect()
+            };
+            $all_series.push(Series::new($name, values));
+        }
+    };
+}
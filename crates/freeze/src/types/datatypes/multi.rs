@@ -66,8 +66,8 @@ pub trait MultiDataset: Sync + Send {
             Chunk::Transaction(chunk) => {
                 self.collect_transaction_chunk(chunk, source, schemas, filter).await
             }
-            Chunk::Address(chunk) => {
-                self.collect_address_chunk(chunk, source, schemas, filter).await
+            Chunk::Address(chunk, blocks) => {
+                self.collect_address_chunk(chunk, blocks, source, schemas, filter).await
             }
         }
     }
@@ -94,14 +94,15 @@ pub trait MultiDataset: Sync + Send {
         panic!("transaction_chunk collection not implemented for {}", self.name())
     }
 
-    /// collect dataset for a particular transaction chunk
+    /// collect dataset for a particular address chunk at each block of a block chunk
     async fn collect_address_chunk(
         &self,
         _chunk: &AddressChunk,
+        _blocks: &BlockChunk,
         _source: &Source,
         _schemas: HashMap<Datatype, Table>,
         _filter: HashMap<Datatype, RowFilter>,
     ) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
-        panic!("transaction_chunk collection not implemented for {}", self.name())
+        panic!("address_chunk collection not implemented for {}", self.name())
     }
 }
@@ -8,61 +8,136 @@ use crate::types::{
     TransactionChunk,
 };
 
+/// Address Appearances Dataset
+pub struct AddressAppearances;
 /// Balance Diffs Dataset
 pub struct BalanceDiffs;
+/// Balances Dataset
+pub struct Balances;
+/// Blobs Dataset
+pub struct Blobs;
 /// Blocks Dataset
 pub struct Blocks;
+/// Code Dataset
+pub struct Code;
 /// Code Diffs Dataset
 pub struct CodeDiffs;
+/// Contracts Dataset
+pub struct Contracts;
+/// Erc1155 Transfers Dataset
+pub struct Erc1155Transfers;
+/// Erc20 Transfers Dataset
+pub struct Erc20Transfers;
+/// Erc721 Transfers Dataset
+pub struct Erc721Transfers;
+/// Eth Calls Dataset
+pub struct EthCalls;
 /// Logs Dataset
 pub struct Logs;
+/// Native Transfers Dataset
+pub struct NativeTransfers;
 /// Nonce Diffs Dataset
 pub struct NonceDiffs;
+/// Nonces Dataset
+pub struct Nonces;
+/// Receipts Dataset
+pub struct Receipts;
+/// Slots Dataset
+pub struct Slots;
 /// Storage Diffs Dataset
 pub struct StorageDiffs;
 /// Traces Dataset
 pub struct Traces;
 /// Transactions Dataset
 pub struct Transactions;
+/// Uncles Dataset
+pub struct Uncles;
 /// VmTraces Dataset
 pub struct VmTraces;
+/// Withdrawals Dataset
+pub struct Withdrawals;
 
 /// enum of possible datatypes that cryo can collect
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Datatype {
+    /// Address Appearances
+    AddressAppearances,
     /// Balance Diffs
     BalanceDiffs,
+    /// Balances
+    Balances,
+    /// Blobs
+    Blobs,
     /// Blocks
     Blocks,
+    /// Code
+    Code,
     /// Code Diffs
     CodeDiffs,
+    /// Contracts
+    Contracts,
+    /// Erc1155 Transfers
+    Erc1155Transfers,
+    /// Erc20 Transfers
+    Erc20Transfers,
+    /// Erc721 Transfers
+    Erc721Transfers,
+    /// Eth Calls
+    EthCalls,
     /// Logs
     Logs,
+    /// Native Transfers
+    NativeTransfers,
     /// Nonce Diffs
     NonceDiffs,
+    /// Nonces
+    Nonces,
+    /// Receipts
+    Receipts,
+    /// Slots
+    Slots,
     /// Transactions
     Transactions,
     /// Traces
     Traces,
     /// Storage Diffs
     StorageDiffs,
+    /// Uncles
+    Uncles,
     /// VmTraces
     VmTraces,
+    /// Withdrawals
+    Withdrawals,
 }
 
 impl Datatype {
     /// get the Dataset struct corresponding to Datatype
     pub fn dataset(&self) -> Box<dyn Dataset> {
         match *self {
+            Datatype::AddressAppearances => Box::new(AddressAppearances),
             Datatype::BalanceDiffs => Box::new(BalanceDiffs),
+            Datatype::Balances => Box::new(Balances),
+            Datatype::Blobs => Box::new(Blobs),
             Datatype::Blocks => Box::new(Blocks),
+            Datatype::Code => Box::new(Code),
             Datatype::CodeDiffs => Box::new(CodeDiffs),
+            Datatype::Contracts => Box::new(Contracts),
+            Datatype::Erc1155Transfers => Box::new(Erc1155Transfers),
+            Datatype::Erc20Transfers => Box::new(Erc20Transfers),
+            Datatype::Erc721Transfers => Box::new(Erc721Transfers),
+            Datatype::EthCalls => Box::new(EthCalls),
             Datatype::Logs => Box::new(Logs),
+            Datatype::NativeTransfers => Box::new(NativeTransfers),
             Datatype::NonceDiffs => Box::new(NonceDiffs),
+            Datatype::Nonces => Box::new(Nonces),
+            Datatype::Receipts => Box::new(Receipts),
+            Datatype::Slots => Box::new(Slots),
             Datatype::Transactions => Box::new(Transactions),
             Datatype::Traces => Box::new(Traces),
             Datatype::StorageDiffs => Box::new(StorageDiffs),
+            Datatype::Uncles => Box::new(Uncles),
             Datatype::VmTraces => Box::new(VmTraces),
+            Datatype::Withdrawals => Box::new(Withdrawals),
         }
     }
 }
@@ -100,8 +175,8 @@ pub trait Dataset: Sync + Send {
             Chunk::Transaction(chunk) => {
                 self.collect_transaction_chunk(chunk, source, schema, filter).await
             }
-            Chunk::Address(chunk) => {
-                self.collect_address_chunk(chunk, source, schema, filter).await
+            Chunk::Address(chunk, blocks) => {
+                self.collect_address_chunk(chunk, blocks, source, schema, filter).await
             }
         }
     }
@@ -128,14 +203,15 @@ pub trait Dataset: Sync + Send {
         panic!("transaction_chunk collection not implemented for {}", self.name())
     }
 
-    /// collect dataset for a particular transaction chunk
+    /// collect dataset for a particular address chunk at each block of a block chunk
     async fn collect_address_chunk(
         &self,
         _chunk: &AddressChunk,
+        _blocks: &BlockChunk,
         _source: &Source,
         _schema: &Table,
         _filter: Option<&RowFilter>,
     ) -> Result<DataFrame, CollectError> {
-        panic!("transaction_chunk collection not implemented for {}", self.name())
+        panic!("address_chunk collection not implemented for {}", self.name())
     }
 }
@@ -30,7 +30,7 @@ pub use datatypes::*;
 pub use files::{ColumnEncoding, FileFormat, FileOutput};
 pub use queries::{MultiQuery, RowFilter, SingleQuery};
 pub use schemas::{ColumnType, Table};
-pub use sources::{RateLimiter, Source};
+pub use sources::{RateLimiter, Source, TraceBackend};
 pub(crate) use summaries::FreezeSummaryAgg;
 pub use summaries::{FreezeChunkSummary, FreezeSummary};
 
@@ -29,12 +29,30 @@ pub struct MultiQuery {
 }
 
 /// Options for fetching logs
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct RowFilter {
     /// topics to filter for
     pub topics: [Option<ValueOrArray<Option<H256>>>; 4],
     /// address to filter for
     pub address: Option<ValueOrArray<H160>>,
+    /// storage slots to read or to filter for
+    pub slots: Option<Vec<H256>>,
+    /// function to call and decode outputs with
+    pub function: Option<ethers::abi::Function>,
+    /// encoded call data of function call
+    pub call_data: Option<Vec<u8>>,
+    /// events to decode logs with
+    pub events: Option<Vec<ethers::abi::Event>>,
+    /// functions to decode call data with
+    pub functions: Option<Vec<ethers::abi::Function>>,
+    /// senders to filter for
+    pub from_address: Option<Vec<H160>>,
+    /// receivers to filter for
+    pub to_address: Option<Vec<H160>>,
+    /// function selectors of call data to filter for
+    pub function_selectors: Option<Vec<[u8; 4]>>,
+    /// opcodes to filter for
+    pub opcodes: Option<Vec<String>>,
 }
 
 impl From<MultiQuery> for SingleQuery {
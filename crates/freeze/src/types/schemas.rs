@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 
+use ethers::abi::ParamType;
 use indexmap::IndexMap;
 use thiserror::Error;
 
@@ -32,6 +33,69 @@ impl Table {
     pub fn columns(&self) -> Vec<&str> {
         self.columns.keys().map(|x| x.as_str()).collect()
     }
+
+    /// add a column for each parameter of the given events
+    ///
+    /// parameters with the same name share a column, which is an error if their types differ,
+    /// columns are only added if selected by `columns` and not excluded by `exclude_columns`
+    pub fn add_event_columns(
+        &mut self,
+        events: &[ethers::abi::Event],
+        binary_column_format: &ColumnEncoding,
+        columns: &Option<Vec<String>>,
+        exclude_columns: &Option<Vec<String>>,
+    ) -> Result<(), SchemaError> {
+        for event in events.iter() {
+            for (index, param) in event.inputs.iter().enumerate() {
+                let column = param_column_name("event", index, &param.name);
+                match columns {
+                    Some(columns) if columns != &["all"] && !columns.contains(&column) => continue,
+                    _ => {}
+                }
+                if exclude_columns.as_ref().map_or(false, |exclude| exclude.contains(&column)) {
+                    continue
+                }
+                let ctype = param_column_type(&param.kind, binary_column_format);
+                match self.columns.get(&column) {
+                    Some(existing) if *existing != ctype => {
+                        return Err(SchemaError::ConflictingColumnType(column))
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.columns.insert(column, ctype);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// name of column holding a decoded abi parameter
+pub(crate) fn param_column_name(prefix: &str, index: usize, name: &str) -> String {
+    match name {
+        "" => format!("{}__arg{}", prefix, index),
+        name => format!("{}__{}", prefix, name),
+    }
+}
+
+/// type of column holding a decoded abi parameter, large integers are stored as strings
+pub(crate) fn param_column_type(
+    kind: &ParamType,
+    binary_column_format: &ColumnEncoding,
+) -> ColumnType {
+    match kind {
+        ParamType::Address | ParamType::Bytes | ParamType::FixedBytes(_) => {
+            match binary_column_format {
+                ColumnEncoding::Binary => ColumnType::Binary,
+                ColumnEncoding::Hex => ColumnType::Hex,
+            }
+        }
+        ParamType::Uint(size) if *size <= 64 => ColumnType::UInt64,
+        ParamType::Int(size) if *size <= 64 => ColumnType::Int64,
+        ParamType::Bool => ColumnType::Boolean,
+        _ => ColumnType::String,
+    }
 }
 
 /// datatype of column
@@ -49,12 +113,18 @@ pub enum ColumnType {
     Float64,
     /// Decimal128 column type
     Decimal128,
+    /// Boolean column type
+    Boolean,
     /// String column type
     String,
     /// Binary column type
     Binary,
     /// Hex column type
     Hex,
+    /// List of Binary column type
+    BinaryList,
+    /// List of Hex column type
+    HexList,
 }
 
 impl ColumnType {
@@ -67,9 +137,12 @@ impl ColumnType {
             ColumnType::Int64 => "int64",
             ColumnType::Float64 => "float64",
             ColumnType::Decimal128 => "decimal128",
+            ColumnType::Boolean => "boolean",
             ColumnType::String => "string",
             ColumnType::Binary => "binary",
             ColumnType::Hex => "hex",
+            ColumnType::BinaryList => "list[binary]",
+            ColumnType::HexList => "list[hex]",
         }
     }
 }
@@ -80,6 +153,10 @@ pub enum SchemaError {
     /// Invalid column being operated on
     #[error("Invalid column")]
     InvalidColumn,
+
+    /// Column used for parameters of different types
+    #[error("Conflicting types for column {0}")]
+    ConflictingColumnType(String),
 }
 
 impl Datatype {
@@ -102,6 +179,9 @@ impl Datatype {
             if (*binary_column_format == ColumnEncoding::Hex) & (ctype == &ColumnType::Binary) {
                 ctype = &ColumnType::Hex;
             }
+            if (*binary_column_format == ColumnEncoding::Hex) & (ctype == &ColumnType::BinaryList) {
+                ctype = &ColumnType::HexList;
+            }
             columns.insert((*column.clone()).to_string(), *ctype);
         }
         let schema = Table { datatype: *self, sort_columns: sort, columns };
@@ -154,3 +234,63 @@ fn compute_used_columns(
         (_, None, None) => default_columns.iter().map(|s| s.to_string()).collect(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::HumanReadableParser;
+
+    use super::*;
+
+    fn event_columns(
+        signatures: &[&str],
+        columns: Option<Vec<&str>>,
+        exclude_columns: Option<Vec<&str>>,
+    ) -> Result<Table, SchemaError> {
+        let events: Vec<_> = signatures
+            .iter()
+            .map(|signature| HumanReadableParser::parse_event(signature).unwrap())
+            .collect();
+        let to_strings = |names: Vec<&str>| names.iter().map(|name| name.to_string()).collect();
+        let mut table =
+            Table { columns: IndexMap::new(), datatype: Datatype::Logs, sort_columns: None };
+        table.add_event_columns(
+            &events,
+            &ColumnEncoding::Hex,
+            &columns.map(to_strings),
+            &exclude_columns.map(to_strings),
+        )?;
+        Ok(table)
+    }
+
+    #[test]
+    fn event_columns_are_named_and_typed_by_param() {
+        let signature = "event Transfer(address indexed from, address indexed, uint256 value)";
+        let table = event_columns(&[signature, "event Approval(string value)"], None, None);
+        let table = table.unwrap();
+        assert_eq!(table.columns(), vec!["event__from", "event__arg1", "event__value"]);
+        assert_eq!(table.column_type("event__from"), Some(ColumnType::Hex));
+        assert_eq!(table.column_type("event__value"), Some(ColumnType::String));
+    }
+
+    #[test]
+    fn event_columns_with_conflicting_types_are_rejected() {
+        let signatures = ["event A(uint256 value)", "event B(bool value)"];
+        let result = event_columns(&signatures, None, None);
+        let error = result.unwrap_err().to_string();
+        assert_eq!(error, "Conflicting types for column event__value");
+    }
+
+    #[test]
+    fn event_columns_respect_column_selection() {
+        let signatures = ["event A(uint256 value)", "event B(bool value)", "event C(address to)"];
+        let table = event_columns(&signatures, Some(vec!["event__to"]), None).unwrap();
+        assert_eq!(table.columns(), vec!["event__to"]);
+
+        let table = event_columns(&signatures[2..], Some(vec!["all"]), None).unwrap();
+        assert_eq!(table.columns(), vec!["event__to"]);
+
+        let signatures = ["event A(uint256 value, address to)"];
+        let table = event_columns(&signatures, None, Some(vec!["event__value"])).unwrap();
+        assert_eq!(table.columns(), vec!["event__to"]);
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::sync::{atomic::AtomicBool, Arc};
 
 use ethers::prelude::*;
 use governor::{
@@ -26,6 +26,19 @@ pub struct Source {
     pub inner_request_size: u64,
     /// Maximum chunks collected concurrently
     pub max_concurrent_chunks: u64,
+    /// rpc namespace used to collect traces
+    pub trace_backend: TraceBackend,
+    /// set once the node has rejected eth_getBlockReceipts as an unknown method
+    pub block_receipts_unsupported: Arc<AtomicBool>,
+}
+
+/// RPC namespace used to collect traces
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TraceBackend {
+    /// parity-style trace_* methods
+    Parity,
+    /// geth-style debug_trace* methods
+    Geth,
 }
 
 // impl Source {
@@ -37,6 +37,12 @@ def parse_cli_args(
     else:
         raise Exception('unknown file_format')
 
+    # filters accept either a single value or a list of values
+    for key in ['contract', 'topic0', 'topic1', 'topic2', 'topic3']:
+        value = kwargs.get(key)
+        if isinstance(value, str):
+            kwargs[key] = [value]  # type: ignore
+
     kwargs['no_verbose'] = not verbose
 
     return kwargs
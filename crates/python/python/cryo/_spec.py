@@ -23,8 +23,13 @@ if typing.TYPE_CHECKING:
     class CryoCliArgs(TypedDict, total=False):
         datatypes: typing.Sequence[Datatype]
         blocks: typing.Sequence[str] | None
+        timestamps: typing.Sequence[str] | None
+        sample: float | None
+        seed: int | None
         align: bool
         reorg_buffer: int
+        address: typing.Sequence[str] | None
+        txs: typing.Sequence[str] | None
         include_columns: typing.Sequence[str] | None
         exclude_columns: typing.Sequence[str] | None
         columns: typing.Sequence[str] | None
@@ -32,6 +37,7 @@ if typing.TYPE_CHECKING:
         sort: typing.Sequence[str] | None
         rpc: str | None
         network_name: str | None
+        trace_backend: str | None
         requests_per_second: int | None
         max_concurrent_requests: int | None
         max_concurrent_chunks: int | None
@@ -47,11 +53,21 @@ if typing.TYPE_CHECKING:
         n_row_groups: int | None
         no_stats: bool
         compression: str | None
-        contract: str | bytes | None
-        topic0: str | bytes | None
-        topic1: str | bytes | None
-        topic2: str | bytes | None
-        topic3: str | bytes | None
+        contract: str | typing.Sequence[str] | None
+        topic0: str | typing.Sequence[str] | None
+        topic1: str | typing.Sequence[str] | None
+        topic2: str | typing.Sequence[str] | None
+        topic3: str | typing.Sequence[str] | None
+        event_signature: typing.Sequence[str] | None
+        function_signature: typing.Sequence[str] | None
+        abi: typing.Sequence[str] | None
+        from_address: typing.Sequence[str] | None
+        to_address: typing.Sequence[str] | None
+        function_selector: typing.Sequence[str] | None
+        opcode: typing.Sequence[str] | None
+        slot: typing.Sequence[str] | None
+        function: str | None
+        inputs: typing.Sequence[str] | None
         inner_request_size: int | None
         no_verbose: bool
 
@@ -10,8 +10,18 @@ datatypes = [
     'blocks',
     'transactions',
     'txs',
+    'receipts',
+    'withdrawals',
+    'blobs',
+    'uncles',
     'logs',
+    'erc20_transfers',
+    'erc721_transfers',
+    'erc1155_transfers',
     'traces',
+    'native_transfers',
+    'contracts',
+    'address_appearances',
     'nonce_diffs',
     'balance_diffs',
     'storage_diffs',
@@ -48,3 +58,48 @@ def test_datatype_hex(datatype, blocks):
 # def test_sort():
 #     raise NotImplementedError()
 
+
+address_datatypes = [
+    'balances',
+    'code',
+    'nonces',
+]
+
+addresses = [['0xd8da6bf26964af9d7eed9e03e53415d37aa96045']]
+
+
+@pytest.mark.parametrize('datatype', address_datatypes)
+@pytest.mark.parametrize('address', addresses)
+def test_address_datatype(datatype, address):
+    output_dir = tempfile.mkdtemp()
+    blocks = ['17_000_000:17_000_010']
+    cryo.collect(datatype, blocks=blocks, address=address)
+    results = cryo.freeze(
+        datatype, blocks=blocks, address=address, output_dir=output_dir
+    )
+    assert results['n_errored'] == 0
+
+
+def test_slots():
+    output_dir = tempfile.mkdtemp()
+    blocks = ['17_000_000:17_000_010']
+    contract = '0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48'
+    slot = ['0x0', '0x1']
+    cryo.collect('slots', blocks=blocks, contract=contract, slot=slot)
+    results = cryo.freeze(
+        'slots', blocks=blocks, contract=contract, slot=slot, output_dir=output_dir
+    )
+    assert results['n_errored'] == 0
+
+
+def test_eth_calls():
+    output_dir = tempfile.mkdtemp()
+    blocks = ['17_000_000:17_000_010']
+    kwargs = {
+        'contract': '0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48',
+        'function': 'balanceOf(address)(uint256)',
+        'inputs': ['0x55fe002aeff02f77364de339a1292923a15844b8'],
+    }
+    cryo.collect('eth_calls', blocks=blocks, **kwargs)
+    results = cryo.freeze('eth_calls', blocks=blocks, output_dir=output_dir, **kwargs)
+    assert results['n_errored'] == 0
@@ -12,6 +12,11 @@ use cryo_freeze::collect;
         *,
         align = false,
         reorg_buffer = 0,
+        address = None,
+        txs = None,
+        timestamps = None,
+        sample = None,
+        seed = None,
         include_columns = None,
         exclude_columns = None,
         columns = None,
@@ -19,6 +24,7 @@ use cryo_freeze::collect;
         sort = None,
         rpc = None,
         network_name = None,
+        trace_backend = None,
         requests_per_second = None,
         max_concurrent_requests = None,
         max_concurrent_chunks = None,
@@ -39,6 +45,16 @@ use cryo_freeze::collect;
         topic1 = None,
         topic2 = None,
         topic3 = None,
+        event_signature = None,
+        function_signature = None,
+        abi = None,
+        from_address = None,
+        to_address = None,
+        function_selector = None,
+        opcode = None,
+        slot = None,
+        function = None,
+        inputs = None,
         inner_request_size = 1,
         no_verbose = false,
     )
@@ -50,6 +66,11 @@ pub fn _collect(
     blocks: Vec<String>,
     align: bool,
     reorg_buffer: u64,
+    address: Option<Vec<String>>,
+    txs: Option<Vec<String>>,
+    timestamps: Option<Vec<String>>,
+    sample: Option<f64>,
+    seed: Option<u64>,
     include_columns: Option<Vec<String>>,
     exclude_columns: Option<Vec<String>>,
     columns: Option<Vec<String>>,
@@ -57,6 +78,7 @@ pub fn _collect(
     sort: Option<Vec<String>>,
     rpc: Option<String>,
     network_name: Option<String>,
+    trace_backend: Option<String>,
     requests_per_second: Option<u32>,
     max_concurrent_requests: Option<u64>,
     max_concurrent_chunks: Option<u64>,
@@ -72,11 +94,21 @@ pub fn _collect(
     n_row_groups: Option<usize>,
     no_stats: bool,
     compression: Vec<String>,
-    contract: Option<String>,
-    topic0: Option<String>,
-    topic1: Option<String>,
-    topic2: Option<String>,
-    topic3: Option<String>,
+    contract: Option<Vec<String>>,
+    topic0: Option<Vec<String>>,
+    topic1: Option<Vec<String>>,
+    topic2: Option<Vec<String>>,
+    topic3: Option<Vec<String>>,
+    event_signature: Option<Vec<String>>,
+    function_signature: Option<Vec<String>>,
+    abi: Option<Vec<String>>,
+    from_address: Option<Vec<String>>,
+    to_address: Option<Vec<String>>,
+    function_selector: Option<Vec<String>>,
+    opcode: Option<Vec<String>>,
+    slot: Option<Vec<String>>,
+    function: Option<String>,
+    inputs: Option<Vec<String>>,
     inner_request_size: u64,
     no_verbose: bool,
 ) -> PyResult<&PyAny> {
@@ -85,6 +117,11 @@ pub fn _collect(
         blocks,
         align,
         reorg_buffer,
+        address,
+        txs,
+        timestamps,
+        sample,
+        seed,
         include_columns,
         exclude_columns,
         columns,
@@ -92,6 +129,7 @@ pub fn _collect(
         sort,
         rpc,
         network_name,
+        trace_backend,
         requests_per_second,
         max_concurrent_requests,
         max_concurrent_chunks,
@@ -112,6 +150,16 @@ pub fn _collect(
         topic1,
         topic2,
         topic3,
+        event_signature,
+        function_signature,
+        abi,
+        from_address,
+        to_address,
+        function_selector,
+        opcode,
+        slot,
+        function,
+        inputs,
         inner_request_size,
         no_verbose,
     };